@@ -11,7 +11,10 @@ use clap::{Parser, Subcommand};
 use dats::context::{DatContext, ZoneName};
 use processor::processor::{DatProcessingState, DatProcessor};
 
-use crate::{DAT_GENERATION_DIR, LOOKUP_TABLE_DIR, RAW_DATA_DIR, ZONE_MAPPING_FILE};
+use crate::{
+    project_settings::ProjectSettings, DAT_GENERATION_DIR, LOOKUP_TABLE_DIR, RAW_DATA_DIR,
+    ZONE_MAPPING_FILE,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -71,9 +74,12 @@ pub fn export_all_dats(project_dir: String) -> Result<()> {
     let zones_mapping: HashMap<u16, ZoneName> = serde_yaml::from_reader(zone_file)
         .map_err(|err| anyhow!("Unable to read zone mapping file: {}", err))?;
 
+    let project_settings = ProjectSettings::load(&project_path);
+
     let dat_context = Arc::new(DatContext::from_path_and_zone_mappings(
         lookup_dir,
         zones_mapping,
+        project_settings.language,
     )?);
 
     let in_dir = project_path.join(RAW_DATA_DIR);
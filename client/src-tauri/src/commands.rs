@@ -5,12 +5,13 @@ use std::{
 };
 
 use anyhow::{anyhow, Result};
+use dats::language::Language;
 use processor::{dat_descriptor::DatDescriptor, processor::DatProcessorMessage};
 use tracing_subscriber::fmt::MakeWriter;
 
 use crate::{
     app_persistence::PersistenceData,
-    dat_query::{self, ZoneInfo},
+    dat_query::{self, MinimapInfo, SoundInfo, ZoneInfo},
     errors::AppError,
     state::{AppState, FileNotification},
     DAT_GENERATION_DIR, LOOKUP_TABLE_DIR, RAW_DATA_DIR, ZONE_MAPPING_FILE,
@@ -34,6 +35,21 @@ pub async fn select_project_folder<'a>(
     state.write().set_project_path(path)
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn get_project_language<'a>(state: AppState<'a>) -> Result<Language, AppError> {
+    Ok(state.read().project_settings.language)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_project_language<'a>(
+    language: Language,
+    state: AppState<'a>,
+) -> Result<Language, AppError> {
+    state.write().set_project_language(language)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn load_persistence_data<'a>(state: AppState<'a>) -> Result<PersistenceData, AppError> {
@@ -55,6 +71,30 @@ pub async fn get_zones_for_type(
     Ok(dat_query::get_zone_ids_for_type(dat_descriptor, dat_context).await)
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn get_minimaps(state: AppState<'_>) -> Result<Vec<MinimapInfo>, AppError> {
+    let dat_context = state
+        .read()
+        .dat_context
+        .clone()
+        .ok_or(anyhow!("No DAT context."))?;
+
+    Ok(dat_query::get_minimaps(dat_context).await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sound_dats(state: AppState<'_>) -> Result<Vec<SoundInfo>, AppError> {
+    let dat_context = state
+        .read()
+        .dat_context
+        .clone()
+        .ok_or(anyhow!("No DAT context."))?;
+
+    Ok(dat_query::get_sound_dats(dat_context))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_misc_dats() -> Result<Vec<DatDescriptor>, AppError> {
@@ -100,6 +140,10 @@ pub async fn get_working_files(state: AppState<'_>) -> Result<Vec<DatDescriptor>
         .into_iter()
         .filter_map(|entry| {
             let entry = entry.ok()?;
+            if !DatDescriptor::is_raw_data_file(entry.path()) {
+                return None;
+            }
+
             DatDescriptor::from_path(&entry.into_path(), &raw_data_dir, &dat_context)
         })
         .collect())
@@ -130,6 +174,10 @@ pub async fn make_all_dats(state: AppState<'_>) -> Result<(), AppError> {
         .into_iter()
         .filter_map(|entry| {
             let entry = entry.ok()?;
+            if !DatDescriptor::is_raw_data_file(entry.path()) {
+                return None;
+            }
+
             DatDescriptor::from_path(&entry.into_path(), &raw_data_dir, &dat_context)
         })
         .for_each(|dat_descriptor| {
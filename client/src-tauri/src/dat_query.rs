@@ -1,11 +1,10 @@
-use std::sync::Arc;
+use std::{fs, path::Path, sync::Arc};
 
 use anyhow::anyhow;
 use dats::{
-    base::{DatByZone, ZoneId},
+    base::{Dat, DatByZone, DatByZoneFloor, ZoneId},
     context::DatContext,
     dat_format::DatFormat,
-    id_mapping::DatIdMapping,
 };
 use processor::dat_descriptor::DatDescriptor;
 use serde::Serialize;
@@ -14,7 +13,10 @@ use tauri::async_runtime;
 use crate::errors::AppError;
 
 pub fn get_misc_dats() -> Vec<DatDescriptor> {
-    vec![DatDescriptor::DataMenu]
+    vec![
+        DatDescriptor::DataMenu,
+        // DatDescriptor::Font, // TODO: DAT id and glyph layout not verified on a retail font DAT yet
+    ]
 }
 
 pub fn get_standalone_string_dats() -> Vec<DatDescriptor> {
@@ -23,6 +25,7 @@ pub fn get_standalone_string_dats() -> Vec<DatDescriptor> {
         DatDescriptor::AbilityDescriptions,
         DatDescriptor::AreaNames,
         DatDescriptor::AreaNamesAlt,
+        DatDescriptor::AutoTranslate,
         DatDescriptor::CharacterSelect,
         DatDescriptor::ChatFilterTypes,
         DatDescriptor::DayNames,
@@ -30,23 +33,29 @@ pub fn get_standalone_string_dats() -> Vec<DatDescriptor> {
         DatDescriptor::EquipmentLocations,
         DatDescriptor::ErrorMessages,
         DatDescriptor::IngameMessages1,
-        // DatDescriptor::IngameMessages2, // TODO: XiStringTable parsing isn't fully supported yet
+        DatDescriptor::IngameMessages2,
         DatDescriptor::JobNames,
         DatDescriptor::KeyItems,
         DatDescriptor::MenuItemsDescription,
         DatDescriptor::MenuItemsText,
+        DatDescriptor::MissionLog,
         DatDescriptor::MoonPhases,
-        // DatDescriptor::PolMessages,  // TODO: XiStringTable parsing isn't fully supported yet
+        DatDescriptor::PolMessages,
+        DatDescriptor::QuestLog,
         DatDescriptor::RaceNames,
         DatDescriptor::RegionNames,
         DatDescriptor::SpellNames,
         DatDescriptor::SpellDescriptions,
         DatDescriptor::StatusInfo,
         DatDescriptor::StatusNames,
-        // DatDescriptor::TimeAndPronouns,  // TODO: XiStringTable parsing isn't fully supported yet
+        DatDescriptor::TimeAndPronouns,
         DatDescriptor::Titles,
         DatDescriptor::Misc1,
         DatDescriptor::Misc2,
+        DatDescriptor::Misc3,
+        DatDescriptor::Misc4,
+        DatDescriptor::Misc5,
+        DatDescriptor::Misc6,
         DatDescriptor::WeatherTypes,
     ]
 }
@@ -55,14 +64,14 @@ pub fn get_item_dats() -> Vec<DatDescriptor> {
     vec![
         DatDescriptor::Armor,
         DatDescriptor::Armor2,
-        // DatDescriptor::Currency, // TODO: can't currently parse this
+        // DatDescriptor::Currency, // TODO: layout not verified on a retail currency DAT yet
         DatDescriptor::GeneralItems,
         DatDescriptor::GeneralItems2,
         DatDescriptor::PuppetItems,
         DatDescriptor::UsableItems,
         DatDescriptor::Weapons,
         DatDescriptor::VouchersAndSlips,
-        // DatDescriptor::Monipulator, // TODO: fields seems to be very different compared to other items
+        // DatDescriptor::Monipulator, // TODO: fields aren't identified yet
         DatDescriptor::Instincts,
     ]
 }
@@ -86,33 +95,27 @@ pub struct ZoneInfo {
     name: String,
 }
 
-async fn get_zone_ids_from_dats<T: DatFormat + 'static>(
-    dat_by_zone: &DatByZone<T>,
+/// Keeps the DATs that exist and match their type, along with the name of their zone.
+async fn get_checked_zone_dats<K: Send + 'static, T: DatFormat + 'static>(
+    dats: impl Iterator<Item = (K, ZoneId, Dat<T>)>,
     dat_context: Arc<DatContext>,
-) -> Vec<ZoneInfo> {
-    let handles = dat_by_zone
-        .map
-        .iter()
-        .filter_map(|(zone_id, dat_id)| {
-            let zone_id = zone_id.clone();
-            let dat_id = dat_id.clone();
+) -> Vec<(K, String)> {
+    let handles = dats
+        .map(|(key, zone_id, dat_id)| {
             let dat_context = dat_context.clone();
 
-            Some(async_runtime::spawn(async move {
+            async_runtime::spawn(async move {
                 let zone_name = dat_context
                     .zone_id_to_name
                     .get(&zone_id)
                     .ok_or(anyhow!("No zone name for ID."))?;
 
                 if dat_context.check_dat(&dat_id).is_ok() {
-                    Ok::<_, AppError>(ZoneInfo {
-                        id: zone_id.clone(),
-                        name: zone_name.display_name.clone(),
-                    })
+                    Ok::<_, AppError>((key, zone_name.display_name.clone()))
                 } else {
                     Err(anyhow!("DAT did not match type."))?
                 }
-            }))
+            })
         })
         .collect::<Vec<_>>();
 
@@ -124,22 +127,120 @@ async fn get_zone_ids_from_dats<T: DatFormat + 'static>(
         .collect()
 }
 
+async fn get_zone_ids_from_dats<T: DatFormat + 'static>(
+    dat_by_zone: &DatByZone<T>,
+    dat_context: Arc<DatContext>,
+) -> Vec<ZoneInfo> {
+    let dats = dat_by_zone
+        .map
+        .iter()
+        .map(|(zone_id, dat_id)| (*zone_id, *zone_id, dat_id.clone()));
+
+    get_checked_zone_dats(dats, dat_context)
+        .await
+        .into_iter()
+        .map(|(id, name)| ZoneInfo { id, name })
+        .collect()
+}
+
 pub async fn get_zone_ids_for_type(
     dat_descriptor: DatDescriptor,
     dat_context: Arc<DatContext>,
 ) -> Vec<ZoneInfo> {
     match dat_descriptor {
         DatDescriptor::EntityNames(_) => {
-            get_zone_ids_from_dats(&DatIdMapping::get().entities, dat_context).await
+            get_zone_ids_from_dats(&dat_context.id_mapping().entities, dat_context).await
         }
         DatDescriptor::Dialog(_) => {
-            get_zone_ids_from_dats(&DatIdMapping::get().dialog, dat_context).await
+            get_zone_ids_from_dats(&dat_context.id_mapping().dialog, dat_context).await
         }
         DatDescriptor::Dialog2(_) => {
-            get_zone_ids_from_dats(&DatIdMapping::get().dialog2, dat_context).await
+            get_zone_ids_from_dats(&dat_context.id_mapping().dialog2, dat_context).await
         }
         _ => {
             vec![]
         }
     }
 }
+
+#[derive(Serialize, specta::Type)]
+pub struct MinimapInfo {
+    id: ZoneId,
+    floor: u8,
+    name: String,
+}
+
+async fn get_floors_from_dats<T: DatFormat + 'static>(
+    dat_by_zone_floor: &DatByZoneFloor<T>,
+    dat_context: Arc<DatContext>,
+) -> Vec<MinimapInfo> {
+    let dats = dat_by_zone_floor
+        .map
+        .iter()
+        .map(|(&(zone_id, floor), dat_id)| ((zone_id, floor), zone_id, dat_id.clone()));
+
+    get_checked_zone_dats(dats, dat_context)
+        .await
+        .into_iter()
+        .map(|((id, floor), name)| MinimapInfo { id, floor, name })
+        .collect()
+}
+
+pub async fn get_minimaps(dat_context: Arc<DatContext>) -> Vec<MinimapInfo> {
+    get_floors_from_dats(&dat_context.id_mapping().minimaps, dat_context).await
+}
+
+#[derive(Serialize, specta::Type)]
+pub struct SoundInfo {
+    name: String,
+    kind: String,
+    dat_descriptor: DatDescriptor,
+}
+
+fn get_sound_files_in_dir(dir: &Path, prefix: &str, extension: &str) -> Vec<(String, u32)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if !path.extension()?.eq_ignore_ascii_case(extension) {
+                return None;
+            }
+
+            let name = path.file_stem()?.to_str()?.to_string();
+            let id = name.strip_prefix(prefix)?.parse().ok()?;
+            Some((name, id))
+        })
+        .collect()
+}
+
+pub fn get_sound_dats(dat_context: Arc<DatContext>) -> Vec<SoundInfo> {
+    let sound_path = dat_context.ffxi_path.join("sound").join("win");
+
+    let music = get_sound_files_in_dir(&sound_path.join("music").join("data"), "music", "bgw")
+        .into_iter()
+        .map(|(name, id)| SoundInfo {
+            name,
+            kind: "Music".to_string(),
+            dat_descriptor: DatDescriptor::Music(id as u16),
+        });
+
+    let sound_effect_dirs = fs::read_dir(sound_path.join("se"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir());
+    let sound_effects = sound_effect_dirs
+        .flat_map(|dir| get_sound_files_in_dir(&dir, "se", "spw"))
+        .map(|(name, id)| SoundInfo {
+            name,
+            kind: "Sound effect".to_string(),
+            dat_descriptor: DatDescriptor::SoundEffect(id),
+        });
+
+    music.chain(sound_effects).collect()
+}
@@ -6,6 +6,7 @@ mod cli;
 mod commands;
 mod dat_query;
 mod errors;
+mod project_settings;
 mod state;
 
 use cli::check_cli;
@@ -23,6 +24,7 @@ pub const RAW_DATA_DIR: &'static str = "raw_data";
 pub const LOOKUP_TABLE_DIR: &'static str = "lookup_tables";
 pub const DAT_GENERATION_DIR: &'static str = "generated_dats";
 pub const ZONE_MAPPING_FILE: &'static str = "zones.yml";
+pub const PROJECT_SETTINGS_FILE: &'static str = "project.yml";
 
 fn main() {
     check_cli();
@@ -33,12 +35,16 @@ fn main() {
             commands::dummy_event_type_gen,
             commands::select_ffxi_folder,
             commands::select_project_folder,
+            commands::get_project_language,
+            commands::set_project_language,
             commands::load_persistence_data,
             commands::get_misc_dats,
             commands::get_standalone_string_dats,
             commands::get_item_dats,
             commands::get_global_dialog_dats,
             commands::get_zones_for_type,
+            commands::get_minimaps,
+            commands::get_sound_dats,
             commands::get_working_files,
             commands::make_all_dats,
             commands::make_dat,
@@ -58,8 +64,12 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::select_ffxi_folder,
             commands::select_project_folder,
+            commands::get_project_language,
+            commands::set_project_language,
             commands::load_persistence_data,
             commands::get_zones_for_type,
+            commands::get_minimaps,
+            commands::get_sound_dats,
             commands::get_misc_dats,
             commands::get_standalone_string_dats,
             commands::get_item_dats,
@@ -0,0 +1,36 @@
+use std::{fs, path::Path};
+
+use dats::language::Language;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::PROJECT_SETTINGS_FILE;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+pub struct ProjectSettings {
+    /// Language of the client DATs that the project is made for.
+    #[serde(default)]
+    pub language: Language,
+}
+
+impl ProjectSettings {
+    fn load_existing_data(project_path: &Path) -> Option<Self> {
+        let data: ProjectSettings = serde_yaml::from_str(
+            &fs::read_to_string(project_path.join(PROJECT_SETTINGS_FILE)).ok()?,
+        )
+        .ok()?;
+
+        Some(data)
+    }
+
+    pub fn load(project_path: &Path) -> Self {
+        Self::load_existing_data(project_path).unwrap_or_default()
+    }
+
+    pub fn save(&self, project_path: &Path) -> Option<()> {
+        let str = serde_yaml::to_string(self).ok()?;
+        fs::write(project_path.join(PROJECT_SETTINGS_FILE), str).ok()?;
+
+        Some(())
+    }
+}
@@ -5,19 +5,23 @@ use std::{
     thread,
 };
 
-use anyhow::Result;
-use dats::context::DatContext;
+use anyhow::{anyhow, Result};
+use dats::{context::DatContext, language::Language};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::RwLock;
 use processor::{dat_descriptor::DatDescriptor, processor::DatProcessor};
 use serde::Serialize;
 use tauri::{async_runtime, App, AppHandle, Manager};
 
-use crate::{app_persistence::PersistenceData, errors::AppError, RAW_DATA_DIR};
+use crate::{
+    app_persistence::PersistenceData, errors::AppError, project_settings::ProjectSettings,
+    RAW_DATA_DIR,
+};
 
 #[derive(Debug)]
 pub struct AppStateData {
     pub project_path: Option<PathBuf>,
+    pub project_settings: ProjectSettings,
     pub dat_context: Option<Arc<DatContext>>,
     pub processor: Arc<DatProcessor>,
     pub persistence: PersistenceData,
@@ -34,10 +38,18 @@ impl AppStateData {
     pub fn new(app: &App) -> Self {
         let persistence = PersistenceData::load();
 
+        let project_path = persistence.recent_projects.get(0).cloned();
+        let project_settings = project_path
+            .as_ref()
+            .map(|project_path| ProjectSettings::load(project_path))
+            .unwrap_or_default();
+
         let dat_context = persistence
             .ffxi_path
             .as_ref()
-            .and_then(|ffxi_path| DatContext::from_ffxi_path(ffxi_path.clone()).ok())
+            .and_then(|ffxi_path| {
+                DatContext::from_ffxi_path(ffxi_path.clone(), project_settings.language).ok()
+            })
             .map(|context| Arc::new(context));
 
         let (tx, rx) = std::sync::mpsc::channel();
@@ -46,8 +58,6 @@ impl AppStateData {
         let handle = app.handle();
         thread::spawn(move || Self::watch_handler(rx, handle));
 
-        let project_path = persistence.recent_projects.get(0).cloned();
-
         if let Some(project_path) = &project_path {
             let _ = watcher.watch(&project_path, RecursiveMode::Recursive);
         }
@@ -67,6 +77,7 @@ impl AppStateData {
         Self {
             dat_context,
             project_path,
+            project_settings,
             persistence,
             watcher,
             processor,
@@ -78,7 +89,10 @@ impl AppStateData {
         ffxi_path: Option<PathBuf>,
     ) -> Result<Option<PathBuf>, AppError> {
         let context = if let Some(ffxi_path) = ffxi_path {
-            Some(Arc::new(DatContext::from_ffxi_path(ffxi_path)?))
+            Some(Arc::new(DatContext::from_ffxi_path(
+                ffxi_path,
+                self.project_settings.language,
+            )?))
         } else {
             None
         };
@@ -102,6 +116,11 @@ impl AppStateData {
         }
 
         self.project_path = project_path.clone();
+        self.project_settings = project_path
+            .as_ref()
+            .map(|project_path| ProjectSettings::load(project_path))
+            .unwrap_or_default();
+        self.update_dat_context_language()?;
 
         if let Some(project_path) = project_path {
             // Remove project from list if it's already there, and then insert it at the front
@@ -126,6 +145,38 @@ impl AppStateData {
         Ok(self.persistence.recent_projects.clone())
     }
 
+    pub fn set_project_language(&mut self, language: Language) -> Result<Language, AppError> {
+        let project_path = self
+            .project_path
+            .as_ref()
+            .ok_or(anyhow!("No project path specified."))?;
+
+        self.project_settings.language = language;
+        self.project_settings
+            .save(project_path)
+            .ok_or(anyhow!("Unable to save project settings."))?;
+        self.update_dat_context_language()?;
+
+        Ok(language)
+    }
+
+    // DATs are looked up for the language of the project, so the context is
+    // recreated when it changes.
+    fn update_dat_context_language(&mut self) -> Result<(), AppError> {
+        let language = self.project_settings.language;
+
+        if let Some(context) = &self.dat_context {
+            if context.language != language {
+                self.dat_context = Some(Arc::new(DatContext::from_ffxi_path(
+                    context.ffxi_path.clone(),
+                    language,
+                )?));
+            }
+        }
+
+        Ok(())
+    }
+
     fn watch_handler(rx: std::sync::mpsc::Receiver<notify::Result<Event>>, app_handle: AppHandle) {
         while let Ok(event) = rx.recv() {
             match event {
@@ -162,9 +213,10 @@ impl AppStateData {
 
         for path in raw_data_paths {
             if let Some(dat_descriptor) = Self::get_file_dat_descriptor(&path, &app_state) {
+                // Removing a file next to a raw data file, like an icon, changes the raw data file.
                 let notification = FileNotification {
                     dat_descriptor,
-                    is_delete,
+                    is_delete: is_delete && DatDescriptor::is_raw_data_file(&path),
                 };
                 let _ = app_handle.emit_all("file-change", notification);
             }
@@ -174,7 +226,7 @@ impl AppStateData {
     }
 
     fn get_file_dat_descriptor(path: &PathBuf, app_state: &AppState) -> Option<DatDescriptor> {
-        if path.is_dir() || path.extension() != Some(OsStr::new("yml")) {
+        if path.is_dir() {
             return None;
         }
 
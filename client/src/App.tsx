@@ -2,7 +2,7 @@ import Sidebar, { NavItem } from "./components/Sidebar";
 import Statusbar from "./components/Statusbar";
 import Home from "./components/Home";
 import { Routes, Route } from "@solidjs/router";
-import { HiSolidAdjustmentsHorizontal, HiSolidChatBubbleLeftRight, HiSolidCog8Tooth, HiSolidPencilSquare, HiSolidShoppingBag, HiSolidUser } from "solid-icons/hi";
+import { HiSolidAdjustmentsHorizontal, HiSolidChatBubbleLeftRight, HiSolidCog8Tooth, HiSolidMap, HiSolidMusicalNote, HiSolidPencilSquare, HiSolidShoppingBag, HiSolidUser } from "solid-icons/hi";
 import DatTable from "./components/DatTable";
 import * as commands from "./bindings";
 import Logs from "./components/Logs";
@@ -41,6 +41,16 @@ const navItems: NavItem[] = [
     path: "/dialog2",
     icon: <HiSolidChatBubbleLeftRight />,
   },
+  {
+    name: "Minimaps",
+    path: "/minimaps",
+    icon: <HiSolidMap />,
+  },
+  {
+    name: "Audio",
+    path: "/audio",
+    icon: <HiSolidMusicalNote />,
+  },
 
   { header: "Other" },
   {
@@ -158,6 +168,32 @@ function App() {
                 )}
               ></Route>
 
+              <Route
+                path="/minimaps"
+                component={() => (
+                  <DatTable
+                    title="Minimaps"
+                    rowsResourceFetcher={() => commands.getMinimaps()}
+                    columns={[{ name: "Name", key: "name" }, { name: "ID", key: "id" }, { name: "Floor", key: "floor" }]}
+                    defaultSortColumn="name"
+                    toDatDescriptor={(minimap) => ({ type: "Minimap", index: [minimap.id, minimap.floor] })}
+                  />
+                )}
+              ></Route>
+
+              <Route
+                path="/audio"
+                component={() => (
+                  <DatTable
+                    title="Audio"
+                    rowsResourceFetcher={() => commands.getSoundDats()}
+                    columns={[{ name: "Name", key: "name" }, { name: "Type", key: "kind" }]}
+                    defaultSortColumn="name"
+                    toDatDescriptor={(sound) => sound.dat_descriptor}
+                  />
+                )}
+              ></Route>
+
               <Route
                 path="/logs"
                 component={Logs}
@@ -22,6 +22,14 @@ export function selectProjectFolder(path: string | null) {
     return invoke()<string[]>("select_project_folder", { path })
 }
 
+export function getProjectLanguage() {
+    return invoke()<Language>("get_project_language")
+}
+
+export function setProjectLanguage(language: Language) {
+    return invoke()<Language>("set_project_language", { language })
+}
+
 export function loadPersistenceData() {
     return invoke()<PersistenceData>("load_persistence_data")
 }
@@ -46,6 +54,14 @@ export function getZonesForType(datDescriptor: DatDescriptor) {
     return invoke()<ZoneInfo[]>("get_zones_for_type", { datDescriptor })
 }
 
+export function getMinimaps() {
+    return invoke()<MinimapInfo[]>("get_minimaps")
+}
+
+export function getSoundDats() {
+    return invoke()<SoundInfo[]>("get_sound_dats")
+}
+
 export function getWorkingFiles() {
     return invoke()<DatDescriptor[]>("get_working_files")
 }
@@ -66,10 +82,13 @@ export function copyLookupTables() {
     return invoke()<null>("copy_lookup_tables")
 }
 
-export type DatDescriptor = { type: "DataMenu" } | { type: "AbilityNames" } | { type: "AbilityDescriptions" } | { type: "AreaNames" } | { type: "AreaNamesAlt" } | { type: "CharacterSelect" } | { type: "ChatFilterTypes" } | { type: "DayNames" } | { type: "Directions" } | { type: "EquipmentLocations" } | { type: "ErrorMessages" } | { type: "IngameMessages1" } | { type: "IngameMessages2" } | { type: "JobNames" } | { type: "KeyItems" } | { type: "MenuItemsDescription" } | { type: "MenuItemsText" } | { type: "MoonPhases" } | { type: "PolMessages" } | { type: "RaceNames" } | { type: "RegionNames" } | { type: "SpellNames" } | { type: "SpellDescriptions" } | { type: "StatusInfo" } | { type: "StatusNames" } | { type: "TimeAndPronouns" } | { type: "Titles" } | { type: "Misc1" } | { type: "Misc2" } | { type: "WeatherTypes" } | { type: "Armor" } | { type: "Armor2" } | { type: "Currency" } | { type: "GeneralItems" } | { type: "GeneralItems2" } | { type: "PuppetItems" } | { type: "UsableItems" } | { type: "Weapons" } | { type: "VouchersAndSlips" } | { type: "Monipulator" } | { type: "Instincts" } | { type: "MonsterSkillNames" } | { type: "StatusNamesDialog" } | { type: "EmoteMessages" } | { type: "SystemMessages1" } | { type: "SystemMessages2" } | { type: "SystemMessages3" } | { type: "SystemMessages4" } | { type: "UnityDialogs" } | { type: "EntityNames"; index: number } | { type: "Dialog"; index: number } | { type: "Dialog2"; index: number }
+export type DatDescriptor = { type: "DataMenu" } | { type: "Font" } | { type: "AbilityNames" } | { type: "AbilityDescriptions" } | { type: "AreaNames" } | { type: "AreaNamesAlt" } | { type: "AutoTranslate" } | { type: "CharacterSelect" } | { type: "ChatFilterTypes" } | { type: "DayNames" } | { type: "Directions" } | { type: "EquipmentLocations" } | { type: "ErrorMessages" } | { type: "IngameMessages1" } | { type: "IngameMessages2" } | { type: "JobNames" } | { type: "KeyItems" } | { type: "MenuItemsDescription" } | { type: "MenuItemsText" } | { type: "MissionLog" } | { type: "MoonPhases" } | { type: "PolMessages" } | { type: "QuestLog" } | { type: "RaceNames" } | { type: "RegionNames" } | { type: "SpellNames" } | { type: "SpellDescriptions" } | { type: "StatusInfo" } | { type: "StatusNames" } | { type: "TimeAndPronouns" } | { type: "Titles" } | { type: "Misc1" } | { type: "Misc2" } | { type: "Misc3" } | { type: "Misc4" } | { type: "Misc5" } | { type: "Misc6" } | { type: "WeatherTypes" } | { type: "Armor" } | { type: "Armor2" } | { type: "Currency" } | { type: "GeneralItems" } | { type: "GeneralItems2" } | { type: "PuppetItems" } | { type: "UsableItems" } | { type: "Weapons" } | { type: "VouchersAndSlips" } | { type: "Monipulator" } | { type: "Instincts" } | { type: "MonsterSkillNames" } | { type: "StatusNamesDialog" } | { type: "EmoteMessages" } | { type: "SystemMessages1" } | { type: "SystemMessages2" } | { type: "SystemMessages3" } | { type: "SystemMessages4" } | { type: "UnityDialogs" } | { type: "EntityNames"; index: number } | { type: "Dialog"; index: number } | { type: "Dialog2"; index: number } | { type: "Minimap"; index: [number, number] } | { type: "Music"; index: number } | { type: "SoundEffect"; index: number }
 export type ZoneInfo = { id: number; name: string }
+export type MinimapInfo = { id: number; floor: number; name: string }
+export type SoundInfo = { name: string; kind: string; dat_descriptor: DatDescriptor }
 export type FileNotification = { dat_descriptor: DatDescriptor; is_delete: boolean }
 export type DatProcessorOutputKind = "Dat" | "Yaml"
+export type Language = "Japanese" | "English"
 export type PersistenceData = { ffxi_path: string | null; recent_projects: string[] }
 export type DatProcessorMessage = { dat_descriptor: DatDescriptor; output_kind: DatProcessorOutputKind; state: DatProcessingState }
 export type DatProcessingState = "Working" | { Finished: string } | { Error: string }
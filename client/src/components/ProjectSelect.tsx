@@ -1,5 +1,9 @@
 import { For, Show, createSignal } from "solid-js";
 import { useData } from "../store";
+import { Language } from "../bindings";
+
+// French and German DATs are not mapped yet.
+const LANGUAGES: Language[] = ["English", "Japanese"];
 
 function ProjectSelect() {
   const {
@@ -8,6 +12,8 @@ function ProjectSelect() {
       getProjectFolder,
       setProjectFolder,
       promptProjectFolder,
+      getProjectLanguage,
+      setProjectLanguage,
     },
   } = useData();
 
@@ -46,6 +52,28 @@ function ProjectSelect() {
         </div>
       </div>
 
+      <Show when={getProjectFolder()}>
+        <div>
+          <h2>Language</h2>
+          <i>
+            The language of the client text the project works on, which decides the DATs it is read from and written to.
+          </i>
+
+          <div>
+            <select
+              value={getProjectLanguage()}
+              onchange={(e) =>
+                setProjectLanguage(e.currentTarget.value as Language)
+              }
+            >
+              <For each={LANGUAGES}>
+                {(language) => <option value={language}>{language}</option>}
+              </For>
+            </select>
+          </div>
+        </div>
+      </Show>
+
       <div>
         Recently opened projects:
         <ul>
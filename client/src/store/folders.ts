@@ -1,8 +1,11 @@
 import { message } from "@tauri-apps/api/dialog";
 import {
+  Language,
+  getProjectLanguage,
   loadPersistenceData,
   selectFfxiFolder,
   selectProjectFolder,
+  setProjectLanguage,
 } from "../bindings";
 import { createEffect, createResource, createSignal } from "solid-js";
 import { promptFolder } from "../util";
@@ -34,6 +37,7 @@ export function createFoldersStore() {
     return selectProjectFolder(path)
       .then((recentFolders) => {
         setRecentProjectFolders(recentFolders);
+        loadProjectLanguage();
       })
       .catch((err) => {
         message(err);
@@ -46,6 +50,31 @@ export function createFoldersStore() {
     string[]
   >([]);
 
+  // Language of the client text the project works on
+  const [getProjectLanguageLocal, setProjectLanguageLocal] =
+    createSignal<Language>("English");
+
+  const loadProjectLanguage = () => {
+    getProjectLanguage()
+      .then((language) => {
+        setProjectLanguageLocal(language);
+      })
+      .catch((err) => {
+        console.error(err);
+      });
+  };
+
+  const updateProjectLanguage = async (language: Language) => {
+    return setProjectLanguage(language)
+      .then((new_language) => {
+        setProjectLanguageLocal(new_language);
+      })
+      .catch((err) => {
+        message(err);
+        console.error(err);
+      });
+  };
+
   // Load data
   const [appPersistence] = createResource(loadPersistenceData);
 
@@ -53,6 +82,9 @@ export function createFoldersStore() {
     setProjectFolderLocal(appPersistence()?.recent_projects[0]);
     setRecentProjectFolders(appPersistence()?.recent_projects ?? []);
     setDatFolderLocal(appPersistence()?.ffxi_path);
+    if (appPersistence()) {
+      loadProjectLanguage();
+    }
   });
 
   const promptDatFolder = () => {
@@ -73,5 +105,8 @@ export function createFoldersStore() {
     promptProjectFolder,
 
     getRecentProjectFolders,
+
+    getProjectLanguage: getProjectLanguageLocal,
+    setProjectLanguage: updateProjectLanguage,
   };
 }
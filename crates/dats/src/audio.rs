@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use common::{
+    byte_walker::{BufferedByteWalker, ByteWalker},
+    vec_byte_walker::VecByteWalker,
+    writing_byte_walker::WritingByteWalker,
+};
+
+pub mod adpcm;
+
+const PCM_FORMAT: u16 = 1;
+const BITS_PER_SAMPLE: u16 = 16;
+const FMT_CHUNK_SIZE: u32 = 16;
+const SMPL_CHUNK_SIZE: u32 = 36 + 24;
+
+/// Chunk that holds the encoded bytes an exported WAV was decoded from.
+const ORIGINAL_BYTES_CHUNK: &[u8; 4] = b"xiad";
+
+/// 16-bit PCM audio, as written to and read from WAV files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wav {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Samples of all channels, interleaved.
+    pub samples: Vec<i16>,
+    /// Frame where playback continues after reaching the end, stored as a sampler loop.
+    pub loop_start: Option<u32>,
+    /// Encoded audio the samples were decoded from, such that unmodified audio
+    /// can be written back exactly as it was.
+    pub original_bytes: Option<Vec<u8>>,
+}
+
+impl Wav {
+    pub fn frame_count(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut walker = VecByteWalker::new();
+        walker.write_str("RIFF");
+        walker.write(0u32);
+        walker.write_str("WAVE");
+
+        walker.write_str("fmt ");
+        walker.write(FMT_CHUNK_SIZE);
+        walker.write(PCM_FORMAT);
+        walker.write(self.channels);
+        walker.write(self.sample_rate);
+        let block_align = self.channels * BITS_PER_SAMPLE / 8;
+        walker.write(self.sample_rate * block_align as u32);
+        walker.write(block_align);
+        walker.write(BITS_PER_SAMPLE);
+
+        walker.write_str("data");
+        walker.write(self.samples.len() as u32 * 2);
+        for sample in &self.samples {
+            walker.write(*sample);
+        }
+
+        if let Some(loop_start) = self.loop_start {
+            walker.write_str("smpl");
+            walker.write(SMPL_CHUNK_SIZE);
+            walker.write(0u32); // Manufacturer
+            walker.write(0u32); // Product
+            walker.write(1_000_000_000 / self.sample_rate.max(1));
+            walker.write(60u32); // MIDI unity note
+            walker.write(0u32); // MIDI pitch fraction
+            walker.write(0u32); // SMPTE format
+            walker.write(0u32); // SMPTE offset
+            walker.write(1u32); // Loop count
+            walker.write(0u32); // Sampler data
+
+            walker.write(0u32); // Cue point ID
+            walker.write(0u32); // Forward loop
+            walker.write(loop_start);
+            walker.write((self.frame_count() as u32).saturating_sub(1));
+            walker.write(0u32); // Fraction
+            walker.write(0u32); // Play forever
+        }
+
+        if let Some(original_bytes) = &self.original_bytes {
+            walker.write_bytes(ORIGINAL_BYTES_CHUNK);
+            walker.write(original_bytes.len() as u32);
+            walker.write_bytes(original_bytes);
+            if original_bytes.len() % 2 == 1 {
+                walker.write(0u8);
+            }
+        }
+
+        let riff_size = walker.offset() as u32 - 8;
+        walker.write_at(4, riff_size);
+
+        walker.into_vec()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut walker = BufferedByteWalker::on(bytes);
+        walker
+            .expect_utf8_str("RIFF")
+            .map_err(|_| anyhow!("Not a RIFF file."))?;
+        walker.step::<u32>()?;
+        walker
+            .expect_utf8_str("WAVE")
+            .map_err(|_| anyhow!("Not a WAVE file."))?;
+
+        let mut format = None;
+        let mut samples = None;
+        let mut loop_start = None;
+        let mut original_bytes = None;
+
+        while walker.remaining() >= 8 {
+            let chunk_id = walker.take_bytes(4)?.to_vec();
+            let chunk_size = walker.step::<u32>()? as usize;
+            if chunk_size > walker.remaining() {
+                return Err(anyhow!("WAV chunk is larger than the file."));
+            }
+
+            let chunk_start = walker.offset();
+            let mut chunk = BufferedByteWalker::on(&bytes[chunk_start..chunk_start + chunk_size]);
+            // Chunks are padded to an even size.
+            walker.skip((chunk_size + chunk_size % 2).min(walker.remaining()));
+
+            match &chunk_id[..] {
+                b"fmt " => {
+                    let audio_format = chunk.step::<u16>()?;
+                    let channels = chunk.step::<u16>()?;
+                    let sample_rate = chunk.step::<u32>()?;
+                    chunk.skip(6);
+                    let bits_per_sample = chunk.step::<u16>()?;
+
+                    if audio_format != PCM_FORMAT || bits_per_sample != BITS_PER_SAMPLE {
+                        return Err(anyhow!(
+                            "Only 16-bit PCM WAV files are supported, got format {} with {} bits.",
+                            audio_format,
+                            bits_per_sample
+                        ));
+                    }
+                    if channels == 0 {
+                        return Err(anyhow!("WAV file without channels."));
+                    }
+                    format = Some((channels, sample_rate));
+                }
+                b"data" => {
+                    samples = Some(
+                        (0..chunk_size / 2)
+                            .map(|_| chunk.step::<i16>())
+                            .collect::<Result<Vec<_>>>()?,
+                    );
+                }
+                b"smpl" => {
+                    chunk.skip(28);
+                    let loop_count = chunk.step::<u32>()?;
+                    chunk.skip(4);
+                    if loop_count > 0 {
+                        chunk.skip(8);
+                        loop_start = Some(chunk.step::<u32>()?);
+                    }
+                }
+                id if id == ORIGINAL_BYTES_CHUNK => {
+                    original_bytes = Some(chunk.take_bytes(chunk_size)?.to_vec());
+                }
+                _ => {}
+            }
+        }
+
+        let (channels, sample_rate) = format.ok_or(anyhow!("No format chunk in WAV file."))?;
+        let samples = samples.ok_or(anyhow!("No data chunk in WAV file."))?;
+
+        Ok(Wav {
+            sample_rate,
+            channels,
+            samples,
+            loop_start,
+            original_bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Wav;
+
+    #[test]
+    pub fn wav_roundtrip() {
+        let wav = Wav {
+            sample_rate: 44100,
+            channels: 2,
+            samples: vec![0, -1, 1000, -1000, i16::MAX, i16::MIN],
+            loop_start: Some(1),
+            original_bytes: Some(vec![1, 2, 3]),
+        };
+
+        let bytes = wav.to_bytes();
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(
+            u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+            bytes.len() - 8
+        );
+        assert_eq!(Wav::from_bytes(&bytes).unwrap(), wav);
+
+        let plain = Wav {
+            loop_start: None,
+            original_bytes: None,
+            ..wav
+        };
+        assert_eq!(Wav::from_bytes(&plain.to_bytes()).unwrap(), plain);
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Result};
+
+/// Size of an ADPCM block: a header byte with the filter and shift, and 16 samples of 4 bits.
+pub const BLOCK_SIZE: usize = 9;
+pub const SAMPLES_PER_BLOCK: usize = 16;
+
+/// Prediction filters of PS-ADPCM, applied to the previous two samples and divided by 64.
+const FILTERS: [(i32, i32); 5] = [(0, 0), (60, 0), (115, -52), (98, -55), (122, -60)];
+const MAX_SHIFT: u8 = 12;
+
+/// The last two decoded samples of a channel, which the next sample is predicted from.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AdpcmState {
+    previous: i32,
+    before_previous: i32,
+}
+
+impl AdpcmState {
+    fn predict(&self, filter: usize) -> i32 {
+        let (current_factor, previous_factor) = FILTERS[filter];
+        (self.previous * current_factor + self.before_previous * previous_factor) >> 6
+    }
+
+    fn push(&mut self, sample: i32) {
+        self.before_previous = self.previous;
+        self.previous = sample;
+    }
+}
+
+fn decode_nibble(nibble: u8, shift: u8, filter: usize, state: &AdpcmState) -> i32 {
+    let delta = (((nibble as i32) << 28) >> 28) << (MAX_SHIFT - shift);
+    (delta + state.predict(filter)).clamp(i16::MIN as i32, i16::MAX as i32)
+}
+
+/// Decodes one block of a channel into 16 samples.
+pub fn decode_block(block: &[u8], state: &mut AdpcmState) -> Result<[i16; SAMPLES_PER_BLOCK]> {
+    let [header, data @ ..] = block else {
+        return Err(anyhow!("Empty ADPCM block."));
+    };
+    if data.len() != SAMPLES_PER_BLOCK / 2 {
+        return Err(anyhow!("ADPCM block of {} bytes.", block.len()));
+    }
+
+    let filter = (header >> 4) as usize;
+    let shift = header & 0x0F;
+    if filter >= FILTERS.len() || shift > MAX_SHIFT {
+        return Err(anyhow!("Invalid ADPCM block header: {:02X}", header));
+    }
+
+    let mut samples = [0; SAMPLES_PER_BLOCK];
+    for (idx, byte) in data.iter().enumerate() {
+        for (nibble_idx, nibble) in [byte & 0x0F, byte >> 4].into_iter().enumerate() {
+            let sample = decode_nibble(nibble, shift, filter, state);
+            state.push(sample);
+            samples[idx * 2 + nibble_idx] = sample as i16;
+        }
+    }
+
+    Ok(samples)
+}
+
+/// Encodes 16 samples into one block, with the filter and shift that give the smallest error.
+pub fn encode_block(
+    samples: &[i16; SAMPLES_PER_BLOCK],
+    state: &mut AdpcmState,
+) -> [u8; BLOCK_SIZE] {
+    let mut best: Option<(i64, [u8; BLOCK_SIZE], AdpcmState)> = None;
+
+    for filter in 0..FILTERS.len() {
+        for shift in 0..=MAX_SHIFT {
+            let mut block = [0; BLOCK_SIZE];
+            block[0] = (filter as u8) << 4 | shift;
+
+            let mut trial_state = *state;
+            let mut error = 0i64;
+            for (idx, sample) in samples.iter().enumerate() {
+                let step = 1 << (MAX_SHIFT - shift);
+                let target = *sample as i32 - trial_state.predict(filter);
+                let nibble = ((target + step / 2).div_euclid(step)).clamp(-8, 7) as u8 & 0x0F;
+
+                let decoded = decode_nibble(nibble, shift, filter, &trial_state);
+                trial_state.push(decoded);
+                error += (decoded - *sample as i32).pow(2) as i64;
+
+                block[1 + idx / 2] |= nibble << (4 * (idx % 2));
+            }
+
+            if !matches!(best, Some((best_error, ..)) if best_error <= error) {
+                best = Some((error, block, trial_state));
+            }
+        }
+    }
+
+    let (_, block, best_state) = best.unwrap();
+    *state = best_state;
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_block, encode_block, AdpcmState, SAMPLES_PER_BLOCK};
+
+    #[test]
+    pub fn encode_decode() {
+        let samples = (0..SAMPLES_PER_BLOCK * 8)
+            .map(|idx| ((idx as f64 / 10.0).sin() * 12000.0) as i16)
+            .collect::<Vec<_>>();
+
+        let mut encode_state = AdpcmState::default();
+        let mut decode_state = AdpcmState::default();
+        for chunk in samples.chunks(SAMPLES_PER_BLOCK) {
+            let block = encode_block(chunk.try_into().unwrap(), &mut encode_state);
+            let decoded = decode_block(&block, &mut decode_state).unwrap();
+
+            for (decoded, original) in decoded.iter().zip(chunk) {
+                assert!((*decoded as i32 - *original as i32).abs() < 512);
+            }
+        }
+
+        assert!(decode_block(&[0x5F, 0, 0, 0, 0, 0, 0, 0, 0], &mut decode_state).is_err());
+    }
+}
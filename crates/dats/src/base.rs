@@ -102,6 +102,41 @@ impl<T: DatFormat> Deref for Dat<T> {
     }
 }
 
+/// A file that is found by its path in the FFXI directory instead of by a DAT ID, like music.
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PathDat<T: DatFormat> {
+    relative_path: PathBuf,
+    _pd: PhantomData<fn() -> T>,
+}
+
+impl<T: DatFormat> Clone for PathDat<T> {
+    fn clone(&self) -> Self {
+        Self {
+            relative_path: self.relative_path.clone(),
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<T: DatFormat> PathDat<T> {
+    pub fn new(relative_path: impl Into<PathBuf>) -> Self {
+        PathDat {
+            relative_path: relative_path.into(),
+            _pd: PhantomData,
+        }
+    }
+
+    pub fn relative_path(&self) -> &PathBuf {
+        &self.relative_path
+    }
+}
+
+impl<T: DatFormat> Display for PathDat<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.relative_path.display())
+    }
+}
+
 #[derive(Debug)]
 pub struct DatByZone<T: DatFormat> {
     pub map: BTreeMap<ZoneId, Dat<T>>,
@@ -132,6 +167,39 @@ impl<T: DatFormat> DatByZone<T> {
     }
 }
 
+/// DATs for zones that have one per floor, like maps.
+#[derive(Debug)]
+pub struct DatByZoneFloor<T: DatFormat> {
+    pub map: BTreeMap<(ZoneId, u8), Dat<T>>,
+}
+
+impl<T: DatFormat> Default for DatByZoneFloor<T> {
+    fn default() -> Self {
+        Self {
+            map: Default::default(),
+        }
+    }
+}
+
+impl<T: DatFormat> DatByZoneFloor<T> {
+    pub fn insert(&mut self, zone_id: usize, floor: u8, dat_id_value: usize) {
+        self.map
+            .insert((zone_id as ZoneId, floor), (dat_id_value as u32).into());
+    }
+
+    pub fn get(&self, zone_id: &ZoneId, floor: u8) -> Option<&Dat<T>> {
+        self.map.get(&(*zone_id, floor))
+    }
+
+    pub fn get_result(&self, zone_id: &ZoneId, floor: u8) -> Result<&Dat<T>> {
+        self.map.get(&(*zone_id, floor)).ok_or(anyhow!(
+            "Did not find DAT for floor {} of zone with ID {}.",
+            floor,
+            zone_id
+        ))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DatPath {
     pub rom_id: u8,
@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use common::{byte_walker::ByteWalker, writing_byte_walker::WritingByteWalker};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{dat_format::DatFormat, serde_base64};
+
+/// Size of the header in front of every chunk. Chunk sizes are always a multiple of this.
+pub const CHUNK_HEADER_SIZE: u32 = 16;
+
+/// Flags of chunks that contain other chunks, up until an end chunk.
+pub const DIRECTORY_FLAGS: u8 = 0x01;
+
+pub const END_CODE: &str = "end\0";
+
+/// The 16 byte header of a chunk: a 4 character code, a size info and 8 reserved bytes.
+///
+/// The size info stores the full chunk size (including the header) in the upper bits,
+/// and 7 bits of flags (the chunk type) in the lower bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkHeader {
+    pub code: String,
+    pub flags: u8,
+    pub content_size: u32,
+    pub reserved: [u8; 8],
+}
+
+impl ChunkHeader {
+    pub fn new(code: &str, flags: u8, content_size: u32) -> Self {
+        ChunkHeader {
+            code: code.to_string(),
+            flags,
+            content_size,
+            reserved: [0; 8],
+        }
+    }
+
+    pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        let code = String::from_utf8(walker.take_bytes(4)?.to_vec())?;
+        let size_info = walker.step::<u32>()?;
+        let chunk_size = (size_info & 0xFFFFFF80) >> 3;
+        if chunk_size < CHUNK_HEADER_SIZE {
+            return Err(anyhow!(
+                "Chunk '{}' is smaller than its header: {} bytes.",
+                code.escape_default(),
+                chunk_size
+            ));
+        }
+
+        let reserved = walker.take_bytes(8)?.try_into()?;
+
+        Ok(ChunkHeader {
+            code,
+            flags: (size_info & 0x7F) as u8,
+            content_size: chunk_size - CHUNK_HEADER_SIZE,
+            reserved,
+        })
+    }
+
+    pub fn size_info(&self) -> u32 {
+        ((self.content_size + CHUNK_HEADER_SIZE) << 3) | (self.flags & 0x7F) as u32
+    }
+
+    pub fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        if self.code.len() != 4 {
+            return Err(anyhow!(
+                "Chunk code must be 4 bytes long, got '{}'.",
+                self.code.escape_default()
+            ));
+        }
+        if self.content_size % CHUNK_HEADER_SIZE != 0 {
+            return Err(anyhow!(
+                "Content of chunk '{}' must be a multiple of {} bytes, got {} bytes.",
+                self.code.escape_default(),
+                CHUNK_HEADER_SIZE,
+                self.content_size
+            ));
+        }
+
+        walker.write_str(&self.code);
+        walker.write(self.size_info());
+        walker.write_bytes(&self.reserved);
+
+        Ok(())
+    }
+}
+
+/// Content of a chunk that was decoded by a registered decoder, which knows how to write itself back.
+pub trait ChunkPayload: Sized {
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()>;
+}
+
+/// Payload for chunk trees that keep every chunk as raw bytes.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum NoPayload {}
+
+impl ChunkPayload for NoPayload {
+    fn write<T: WritingByteWalker>(&self, _walker: &mut T) -> Result<()> {
+        match *self {}
+    }
+}
+
+pub type ChunkDecoder<P> = fn(&ChunkHeader, &[u8]) -> Result<P>;
+
+/// Decoders per chunk code. Chunks without a decoder are kept as raw bytes.
+pub struct ChunkDecoders<P> {
+    decoders: HashMap<String, ChunkDecoder<P>>,
+}
+
+impl<P> Default for ChunkDecoders<P> {
+    fn default() -> Self {
+        ChunkDecoders {
+            decoders: HashMap::new(),
+        }
+    }
+}
+
+impl<P> ChunkDecoders<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(mut self, code: &str, decoder: ChunkDecoder<P>) -> Self {
+        self.decoders.insert(code.to_string(), decoder);
+        self
+    }
+
+    pub fn get(&self, code: &str) -> Option<ChunkDecoder<P>> {
+        self.decoders.get(code).copied()
+    }
+}
+
+fn is_zero(reserved: &[u8; 8]) -> bool {
+    reserved.iter().all(|b| *b == 0)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Chunk<P> {
+    pub code: String,
+    pub flags: u8,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub reserved: [u8; 8],
+    pub content: ChunkContent<P>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ChunkContent<P> {
+    Raw(#[serde(with = "serde_base64")] Vec<u8>),
+    Decoded(P),
+    Directory {
+        #[serde(with = "serde_base64")]
+        header: Vec<u8>,
+        children: Vec<Chunk<P>>,
+    },
+}
+
+impl<P: ChunkPayload> Chunk<P> {
+    pub fn parse<T: ByteWalker>(walker: &mut T, decoders: &ChunkDecoders<P>) -> Result<Self> {
+        let header = ChunkHeader::parse(walker)?;
+        Self::parse_content(header, walker, decoders)
+    }
+
+    /// Parses the content of a chunk whose header was already read.
+    /// For directories, this includes all chunks up until the matching end chunk.
+    pub fn parse_content<T: ByteWalker>(
+        header: ChunkHeader,
+        walker: &mut T,
+        decoders: &ChunkDecoders<P>,
+    ) -> Result<Self> {
+        if header.code == END_CODE {
+            return Err(anyhow!(
+                "Unexpected end chunk at offset {}.",
+                walker.offset() - CHUNK_HEADER_SIZE as usize
+            ));
+        }
+
+        let bytes = walker.take_bytes(header.content_size as usize)?;
+
+        let content = if let Some(decoder) = decoders.get(&header.code) {
+            ChunkContent::Decoded(decoder(&header, bytes).map_err(|err| {
+                anyhow!(
+                    "Failed to decode chunk '{}': {}",
+                    header.code.escape_default(),
+                    err
+                )
+            })?)
+        } else if header.flags == DIRECTORY_FLAGS {
+            let header_bytes = bytes.to_vec();
+
+            let mut children = vec![];
+            loop {
+                let child_header = ChunkHeader::parse(walker)?;
+                if child_header.code == END_CODE {
+                    if child_header != ChunkHeader::new(END_CODE, 0, 0) {
+                        return Err(anyhow!(
+                            "Unsupported end chunk in '{}': {:?}",
+                            header.code.escape_default(),
+                            child_header
+                        ));
+                    }
+                    break;
+                }
+                children.push(Self::parse_content(child_header, walker, decoders)?);
+            }
+
+            ChunkContent::Directory {
+                header: header_bytes,
+                children,
+            }
+        } else {
+            ChunkContent::Raw(bytes.to_vec())
+        };
+
+        Ok(Chunk {
+            code: header.code,
+            flags: header.flags,
+            reserved: header.reserved,
+            content,
+        })
+    }
+
+    pub fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        let header_offset = walker.offset();
+        walker.skip(CHUNK_HEADER_SIZE as usize);
+
+        let content_start = walker.offset();
+        match &self.content {
+            ChunkContent::Raw(bytes) => walker.write_bytes(bytes),
+            ChunkContent::Decoded(payload) => {
+                payload.write(walker)?;
+
+                // Decoded content is padded to keep the chunks aligned
+                let len = walker.offset() - content_start;
+                let padding = (CHUNK_HEADER_SIZE as usize - len % CHUNK_HEADER_SIZE as usize)
+                    % CHUNK_HEADER_SIZE as usize;
+                walker.write_bytes(&vec![0; padding]);
+            }
+            ChunkContent::Directory { header, .. } => walker.write_bytes(header),
+        }
+        let content_end = walker.offset();
+
+        let header = ChunkHeader {
+            code: self.code.clone(),
+            flags: self.flags,
+            content_size: (content_end - content_start) as u32,
+            reserved: self.reserved,
+        };
+        walker.goto_usize(header_offset);
+        header.write(walker)?;
+        walker.goto_usize(content_end);
+
+        if let ChunkContent::Directory { children, .. } = &self.content {
+            for child in children {
+                child.write(walker)?;
+            }
+            ChunkHeader::new(END_CODE, 0, 0).write(walker)?;
+        }
+
+        Ok(())
+    }
+
+    fn collect<'a>(&'a self, chunks: &mut Vec<&'a Chunk<P>>) {
+        chunks.push(self);
+        if let ChunkContent::Directory { children, .. } = &self.content {
+            children.iter().for_each(|child| child.collect(chunks));
+        }
+    }
+}
+
+/// The chunks of a chunked DAT, like menus, zones and models.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ChunkTree<P> {
+    pub chunks: Vec<Chunk<P>>,
+}
+
+impl<P: ChunkPayload> ChunkTree<P> {
+    pub fn parse<T: ByteWalker>(walker: &mut T, decoders: &ChunkDecoders<P>) -> Result<Self> {
+        let mut chunks = vec![];
+        while walker.remaining() > 0 {
+            chunks.push(Chunk::parse(walker, decoders)?);
+        }
+
+        Ok(ChunkTree { chunks })
+    }
+
+    pub fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        for chunk in &self.chunks {
+            chunk.write(walker)?;
+        }
+
+        Ok(())
+    }
+
+    /// All chunks in the tree, depth first.
+    pub fn walk(&self) -> Vec<&Chunk<P>> {
+        let mut chunks = vec![];
+        self.chunks
+            .iter()
+            .for_each(|chunk| chunk.collect(&mut chunks));
+        chunks
+    }
+}
+
+impl DatFormat for ChunkTree<NoPayload> {
+    fn from<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        ChunkTree::parse(walker, &ChunkDecoders::new())
+    }
+
+    fn check_type<T: ByteWalker>(walker: &mut T) -> Result<()> {
+        let header = ChunkHeader::parse(walker)?;
+        if header.content_size as usize > walker.remaining() {
+            return Err(anyhow!(
+                "Chunk '{}' is larger than the DAT.",
+                header.code.escape_default()
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        self.write(walker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use common::{
+        byte_walker::{BufferedByteWalker, ByteWalker},
+        vec_byte_walker::VecByteWalker,
+        writing_byte_walker::WritingByteWalker,
+    };
+    use serde_derive::{Deserialize, Serialize};
+
+    use crate::dat_format::DatFormat;
+
+    use super::{ChunkContent, ChunkDecoders, ChunkHeader, ChunkPayload, ChunkTree, NoPayload};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Numbers(Vec<u32>);
+
+    impl ChunkPayload for Numbers {
+        fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+            self.0.iter().for_each(|number| walker.write(*number));
+            Ok(())
+        }
+    }
+
+    fn decode_numbers(_header: &ChunkHeader, bytes: &[u8]) -> Result<Numbers> {
+        let mut walker = BufferedByteWalker::on(bytes);
+        let mut numbers = vec![];
+        while walker.remaining() > 0 {
+            numbers.push(walker.step::<u32>()?);
+        }
+        Ok(Numbers(numbers))
+    }
+
+    fn to_bytes(tree: &ChunkTree<Numbers>) -> Vec<u8> {
+        let mut walker = VecByteWalker::new();
+        tree.write(&mut walker).unwrap();
+        walker.into_vec()
+    }
+
+    #[test]
+    pub fn menu_roundtrip() {
+        let mut dat_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        dat_path.push("resources/test/menu.DAT");
+
+        let bytes = std::fs::read(&dat_path).unwrap();
+        let res = ChunkTree::<NoPayload>::from_path_checked(&dat_path).unwrap();
+
+        assert_eq!(res.chunks.len(), 1);
+        assert_eq!(res.chunks[0].code, "menu");
+        let codes = res
+            .walk()
+            .iter()
+            .map(|chunk| chunk.code.as_str())
+            .collect::<Vec<_>>();
+        assert!(codes.contains(&"comm"));
+        assert!(codes.contains(&"mgc_"));
+
+        assert_eq!(res.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    pub fn decoded_roundtrip() {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(b"dir_");
+        bytes.extend_from_slice(&((32u32 << 3) | 1).to_le_bytes());
+        bytes.extend_from_slice(&[0; 8]);
+        bytes.extend_from_slice(&[0xAB; 16]);
+
+        bytes.extend_from_slice(b"nums");
+        bytes.extend_from_slice(&((32u32 << 3) | 0x42).to_le_bytes());
+        bytes.extend_from_slice(&[0; 8]);
+        (1u32..=4).for_each(|number| bytes.extend_from_slice(&number.to_le_bytes()));
+
+        bytes.extend_from_slice(b"what");
+        bytes.extend_from_slice(&((32u32 << 3) | 0x7F).to_le_bytes());
+        bytes.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        bytes.extend_from_slice(&[0xCD; 16]);
+
+        bytes.extend_from_slice(b"end\0");
+        bytes.extend_from_slice(&(16u32 << 3).to_le_bytes());
+        bytes.extend_from_slice(&[0; 8]);
+
+        let decoders = ChunkDecoders::new().register("nums", decode_numbers);
+        let mut tree = ChunkTree::parse(&mut BufferedByteWalker::on(&bytes), &decoders).unwrap();
+        assert_eq!(to_bytes(&tree), bytes);
+
+        let ChunkContent::Directory { children, .. } = &mut tree.chunks[0].content else {
+            panic!("Expected a directory");
+        };
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[1].flags, 0x7F);
+        assert_eq!(children[1].reserved, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let ChunkContent::Decoded(numbers) = &mut children[0].content else {
+            panic!("Expected decoded numbers");
+        };
+        assert_eq!(numbers.0, vec![1, 2, 3, 4]);
+
+        // Decoded content that doesn't fill the chunk is padded
+        numbers.0.push(5);
+        let written = to_bytes(&tree);
+        assert_eq!(written.len(), bytes.len() + 16);
+        let res = ChunkTree::parse(&mut BufferedByteWalker::on(&written), &decoders).unwrap();
+        assert_eq!(res.walk().len(), 3);
+    }
+}
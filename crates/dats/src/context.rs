@@ -3,22 +3,28 @@ use std::{
     fs::File,
     io::{Read, Seek, SeekFrom},
     path::PathBuf,
+    sync::{Arc, OnceLock},
 };
 
 use crate::{
-    base::{Dat, DatError, DatId, DatPath, ZoneId},
+    base::{Dat, DatError, DatId, DatPath, PathDat, ZoneId},
     dat_format::DatFormat,
     formats::dmsg2_string_table::Dmsg2Content,
     id_mapping::DatIdMapping,
+    language::Language,
     sanitize_filename::sanitize_filename,
 };
 use anyhow::{anyhow, Result};
+use encoding::auto_translate::AutoTranslatePhrases;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub struct DatContext {
     pub ffxi_path: PathBuf,
     pub id_map: HashMap<DatId, DatPath>,
+    pub language: Language,
+    id_mapping: &'static DatIdMapping,
+    auto_translate_phrases: OnceLock<Arc<AutoTranslatePhrases>>,
 
     pub zone_name_to_id_map: HashMap<String, ZoneId>,
     pub zone_id_to_name: HashMap<ZoneId, ZoneName>,
@@ -37,8 +43,8 @@ pub struct ExtractedDat<T> {
 }
 
 impl DatContext {
-    pub fn from_ffxi_path(ffxi_path: PathBuf) -> Result<Self> {
-        let mut context = Self::from_path_without_zone_mappings(ffxi_path)?;
+    pub fn from_ffxi_path(ffxi_path: PathBuf, language: Language) -> Result<Self> {
+        let mut context = Self::from_path_without_zone_mappings(ffxi_path, language)?;
 
         context.build_zone_mappings()?;
 
@@ -48,8 +54,9 @@ impl DatContext {
     pub fn from_path_and_zone_mappings(
         ffxi_path: PathBuf,
         zone_id_to_name: HashMap<ZoneId, ZoneName>,
+        language: Language,
     ) -> Result<Self> {
-        let mut context = Self::from_path_without_zone_mappings(ffxi_path)?;
+        let mut context = Self::from_path_without_zone_mappings(ffxi_path, language)?;
 
         context.zone_id_to_name = zone_id_to_name;
 
@@ -62,22 +69,46 @@ impl DatContext {
         Ok(context)
     }
 
-    fn from_path_without_zone_mappings(mut ffxi_path: PathBuf) -> Result<Self> {
+    fn from_path_without_zone_mappings(mut ffxi_path: PathBuf, language: Language) -> Result<Self> {
         ffxi_path = Self::find_ffxi_path(ffxi_path)?;
 
+        let id_mapping = DatIdMapping::get(language);
         let id_map = Self::build_rom_id_map(&ffxi_path)?;
 
         Ok(Self {
             ffxi_path,
             id_map,
+            language,
+            id_mapping,
+            auto_translate_phrases: Default::default(),
             zone_name_to_id_map: Default::default(),
             zone_id_to_name: Default::default(),
         })
     }
 
+    /// The DATs that hold each kind of content in the language of the context.
+    pub fn id_mapping(&self) -> &'static DatIdMapping {
+        self.id_mapping
+    }
+
+    /// The phrase texts of the auto-translate dictionary, which is loaded when first needed.
+    pub fn auto_translate_phrases(&self) -> Result<Arc<AutoTranslatePhrases>> {
+        if let Some(phrases) = self.auto_translate_phrases.get() {
+            return Ok(phrases.clone());
+        }
+
+        let auto_translate = self
+            .get_data_from_dat(&self.id_mapping().auto_translate)
+            .map_err(|err| anyhow!("Could not load auto-translate phrases: {err}"))?;
+        Ok(self
+            .auto_translate_phrases
+            .get_or_init(|| Arc::new(auto_translate.dat.phrases().collect()))
+            .clone())
+    }
+
     fn build_zone_mappings(&mut self) -> Result<()> {
         // Initialize the mappings between zone ID and name
-        let zone_data = self.get_data_from_dat(&DatIdMapping::get().area_names)?;
+        let zone_data = self.get_data_from_dat(&self.id_mapping().area_names)?;
 
         let mut previous_names = HashSet::new();
         for (zone_id, (_, zone_string_list)) in zone_data.dat.lists.into_iter().enumerate() {
@@ -249,6 +280,20 @@ impl DatContext {
             .map_err(|err| DatError::DatLoadFailed(id.into(), err))
     }
 
+    pub fn get_data_from_path_dat<T: DatFormat>(
+        &self,
+        dat: &PathDat<T>,
+    ) -> Result<ExtractedDat<T>> {
+        let path = self.ffxi_path.join(dat.relative_path());
+        T::from_path(&path)
+            .map(|data| ExtractedDat { dat: data, path })
+            .map_err(|err| anyhow!("Failed to load data for {}: {}", dat, err))
+    }
+
+    pub fn check_path_dat<T: DatFormat>(&self, dat: &PathDat<T>) -> Result<()> {
+        T::check_path(&self.ffxi_path.join(dat.relative_path()))
+    }
+
     pub fn get_dat_path(&self, id: impl Into<DatId>) -> Result<PathBuf, DatError> {
         id.into().get_ffxi_dat_path(self)
     }
@@ -12,10 +12,24 @@ use std::{
     path::PathBuf,
 };
 
+use crate::external_files::ExternalFiles;
+
 pub trait DatFormat: Sized {
     fn from<T: ByteWalker>(walker: &mut T) -> Result<Self>;
     fn check_type<T: ByteWalker>(walker: &mut T) -> Result<()>;
     fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()>;
+
+    /// Moves data that is better edited outside of the raw data file, like images,
+    /// into external files, and keeps references to them instead.
+    fn export_external_files(&mut self, _files: &ExternalFiles) -> Result<()> {
+        Ok(())
+    }
+
+    /// Reads back the data from the external files referenced by `export_external_files`.
+    fn import_external_files(&mut self, _files: &ExternalFiles) -> Result<()> {
+        Ok(())
+    }
+
     fn to_bytes(&self) -> Result<Vec<u8>> {
         let mut walker = VecByteWalker::new();
         self.write(&mut walker)?;
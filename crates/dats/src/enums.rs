@@ -232,3 +232,30 @@ pub enum EnglishArticle {
     PairOf = 2,
     SuitsOf = 3,
 }
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, FromPrimitive, IntoPrimitive,
+)]
+#[repr(u32)]
+pub enum FrenchGender {
+    Masculine = 0,
+    Feminine = 1,
+
+    #[num_enum(catch_all)]
+    #[serde(untagged)]
+    Unknown(u32),
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, FromPrimitive, IntoPrimitive,
+)]
+#[repr(u32)]
+pub enum GermanGender {
+    Masculine = 0,
+    Feminine = 1,
+    Neuter = 2,
+
+    #[num_enum(catch_all)]
+    #[serde(untagged)]
+    Unknown(u32),
+}
@@ -0,0 +1,110 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::image::XiImage;
+
+/// Extension of images that are exported as their raw bytes, because they couldn't be decoded.
+const RAW_IMAGE_EXTENSION: &str = "bin";
+
+/// Files that are kept next to a raw data file instead of inside it, like images.
+///
+/// For a raw data file at `items/weapons.yml`, the files are placed under `items/weapons/`,
+/// and they are referenced by a path relative to the raw data file, e.g. `weapons/icons/1.png`.
+#[derive(Debug, Clone)]
+pub struct ExternalFiles {
+    base_dir: PathBuf,
+    name: String,
+}
+
+impl ExternalFiles {
+    pub fn for_raw_data_path(raw_data_path: &Path) -> Result<Self> {
+        let base_dir = raw_data_path
+            .parent()
+            .ok_or(anyhow!(
+                "No parent directory for {}",
+                raw_data_path.display()
+            ))?
+            .to_path_buf();
+
+        let name = raw_data_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or(anyhow!("No file name for {}", raw_data_path.display()))?
+            .to_string();
+
+        Ok(Self { base_dir, name })
+    }
+
+    /// Writes the bytes to the given path, and returns the reference to use in the raw data file.
+    pub fn write(&self, relative_path: &str, bytes: &[u8]) -> Result<String> {
+        let reference = format!("{}/{}", self.name, relative_path);
+        let path = self.base_dir.join(&reference);
+
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, bytes)
+            .map_err(|err| anyhow!("Could not write file at {}: {}", path.display(), err))?;
+
+        Ok(reference)
+    }
+
+    /// Reads the bytes of a reference from the raw data file.
+    pub fn read(&self, reference: &str) -> Result<Vec<u8>> {
+        let path = self.base_dir.join(reference);
+        fs::read(&path).map_err(|err| anyhow!("Could not read file at {}: {}", path.display(), err))
+    }
+
+    /// Writes an image as a PNG, and returns the reference to use in the raw data file.
+    ///
+    /// Images that can't be decoded are written as their raw bytes instead, so that
+    /// a single unsupported image doesn't keep the rest of the file from being exported.
+    pub fn write_image(&self, relative_stem: &str, bytes: &[u8]) -> Result<String> {
+        match XiImage::bytes_to_png(bytes) {
+            Ok(png_bytes) => self.write(&format!("{relative_stem}.png"), &png_bytes),
+            Err(_) => self.write(&format!("{relative_stem}.{RAW_IMAGE_EXTENSION}"), bytes),
+        }
+    }
+
+    /// Reads the image bytes of a reference written by `write_image`.
+    pub fn read_image(
+        &self,
+        reference: &str,
+        template: &XiImage,
+        max_size: usize,
+    ) -> Result<Vec<u8>> {
+        let bytes = self.read(reference)?;
+
+        if reference.ends_with(&format!(".{RAW_IMAGE_EXTENSION}")) {
+            return Ok(bytes);
+        }
+
+        XiImage::png_to_bytes(&bytes, template, max_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::image::{XiImage, XiImageFormat};
+
+    use super::ExternalFiles;
+
+    #[test]
+    pub fn undecodable_image_is_kept_raw() {
+        let dir = std::env::temp_dir().join("external_files_undecodable_image");
+        let files = ExternalFiles::for_raw_data_path(&dir.join("items.yml")).unwrap();
+        let bytes = vec![0xAB; 16];
+
+        let reference = files.write_image("icons/1", &bytes).unwrap();
+        assert_eq!(reference, "items/icons/1.bin");
+
+        let template = XiImage::template(XiImageFormat::BitmapA, "menu    ", "item    ", 8);
+        assert_eq!(files.read_image(&reference, &template, 0).unwrap(), bytes);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
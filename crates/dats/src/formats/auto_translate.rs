@@ -0,0 +1,283 @@
+use anyhow::{anyhow, Result};
+use common::{byte_walker::ByteWalker, writing_byte_walker::WritingByteWalker};
+use encoding::{
+    auto_translate::{AutoTranslateId, AUTO_TRANSLATE_TYPE},
+    decoder::Decoder,
+    encoder::Encoder,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::dat_format::DatFormat;
+
+/// The auto-translate dictionary for one language.
+///
+/// Every category starts with its resource ID `[0x02, language, category, 0x00]`, its name and
+/// the byte size of its phrases. Each phrase has its own resource ID, followed by its text and an
+/// alternate text. Texts are prefixed with their byte length, and end with a 0x00 byte.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AutoTranslateTable {
+    pub language: u8,
+    pub categories: Vec<AutoTranslateCategory>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoTranslateCategory {
+    pub id: u8,
+    pub name: String,
+    pub phrases: Vec<AutoTranslatePhrase>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoTranslatePhrase {
+    pub id: u8,
+    pub text: String,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
+    pub alt_text: String,
+}
+
+fn parse_resource_id<T: ByteWalker>(walker: &mut T) -> Result<AutoTranslateId> {
+    walker.expect_msg::<u8>(AUTO_TRANSLATE_TYPE, "Auto-translate resource type")?;
+
+    Ok(AutoTranslateId {
+        language: walker.step()?,
+        category: walker.step()?,
+        phrase: walker.step()?,
+    })
+}
+
+fn parse_text<T: ByteWalker>(walker: &mut T) -> Result<String> {
+    let len = walker.step::<u8>()?;
+    Decoder::decode_simple(walker.take_bytes(len as usize)?)
+}
+
+fn write_text<T: WritingByteWalker>(walker: &mut T, text: &str) -> Result<()> {
+    let mut bytes = Encoder::encode_simple(text)?;
+    bytes.push(0x00);
+
+    if bytes.len() > u8::MAX as usize {
+        return Err(anyhow!(
+            "Auto-translate text is too long ({} bytes): {}",
+            bytes.len(),
+            text
+        ));
+    }
+
+    walker.write(bytes.len() as u8);
+    walker.write_bytes(&bytes);
+
+    Ok(())
+}
+
+impl AutoTranslateCategory {
+    fn parse<T: ByteWalker>(walker: &mut T) -> Result<(u8, AutoTranslateCategory)> {
+        let category_id = parse_resource_id(walker)?;
+        if category_id.phrase != 0 {
+            return Err(anyhow!(
+                "Expected category ID to end with 0x00, got 0x{:02X}",
+                category_id.phrase
+            ));
+        }
+
+        let name = parse_text(walker)?;
+
+        let phrases_bytes = walker.step::<u32>()? as usize;
+        let phrases_end = walker.offset() + phrases_bytes;
+        if phrases_end > walker.len() {
+            return Err(anyhow!(
+                "Phrases of category {} ({} bytes) don't fit in the DAT.",
+                category_id.category,
+                phrases_bytes
+            ));
+        }
+
+        let mut phrases = vec![];
+        while walker.offset() < phrases_end {
+            let phrase_id = parse_resource_id(walker)?;
+            if phrase_id.language != category_id.language
+                || phrase_id.category != category_id.category
+            {
+                return Err(anyhow!(
+                    "Phrase {:02X?} does not belong to category {:02X?}",
+                    phrase_id.to_bytes(),
+                    category_id.to_bytes()
+                ));
+            }
+
+            phrases.push(AutoTranslatePhrase {
+                id: phrase_id.phrase,
+                text: parse_text(walker)?,
+                alt_text: parse_text(walker)?,
+            });
+        }
+
+        Ok((
+            category_id.language,
+            AutoTranslateCategory {
+                id: category_id.category,
+                name,
+                phrases,
+            },
+        ))
+    }
+
+    fn write<T: WritingByteWalker>(&self, walker: &mut T, language: u8) -> Result<()> {
+        let id = AutoTranslateId {
+            language,
+            category: self.id,
+            phrase: 0,
+        };
+        walker.write_bytes(&id.to_bytes());
+        write_text(walker, &self.name)?;
+
+        let phrases_bytes_offset = walker.offset();
+        walker.skip(4); // Reserved for phrases byte size
+
+        for phrase in &self.phrases {
+            let id = AutoTranslateId {
+                phrase: phrase.id,
+                ..id
+            };
+            walker.write_bytes(&id.to_bytes());
+            write_text(walker, &phrase.text)?;
+            write_text(walker, &phrase.alt_text)?;
+        }
+
+        let phrases_end = walker.offset();
+        walker.write_at(
+            phrases_bytes_offset,
+            (phrases_end - phrases_bytes_offset - 4) as u32,
+        );
+
+        Ok(())
+    }
+}
+
+impl AutoTranslateTable {
+    pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        let mut language = None;
+        let mut categories = vec![];
+
+        while walker.remaining() > 0 {
+            let (category_language, category) = AutoTranslateCategory::parse(walker)
+                .map_err(|err| anyhow!("Failed to parse category {}: {err}", categories.len()))?;
+
+            if *language.get_or_insert(category_language) != category_language {
+                return Err(anyhow!(
+                    "Category {} is for language {}, expected {}.",
+                    category.id,
+                    category_language,
+                    language.unwrap()
+                ));
+            }
+
+            categories.push(category);
+        }
+
+        Ok(AutoTranslateTable {
+            language: language.ok_or(anyhow!("No auto-translate categories found."))?,
+            categories,
+        })
+    }
+
+    pub fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        for category in &self.categories {
+            category.write(walker, self.language)?;
+        }
+
+        Ok(())
+    }
+
+    /// All phrase texts by their IDs, as used in auto-translate references in text.
+    pub fn phrases(&self) -> impl Iterator<Item = (AutoTranslateId, String)> + '_ {
+        self.categories.iter().flat_map(move |category| {
+            category.phrases.iter().map(move |phrase| {
+                (
+                    AutoTranslateId {
+                        language: self.language,
+                        category: category.id,
+                        phrase: phrase.id,
+                    },
+                    phrase.text.clone(),
+                )
+            })
+        })
+    }
+}
+
+impl DatFormat for AutoTranslateTable {
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        self.write(walker)
+    }
+
+    fn from<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        AutoTranslateTable::parse(walker)
+    }
+
+    fn check_type<T: ByteWalker>(walker: &mut T) -> Result<()> {
+        // Parse one category to check.
+        AutoTranslateCategory::parse(walker)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding::auto_translate::AutoTranslateId;
+
+    use crate::dat_format::DatFormat;
+
+    use super::{AutoTranslateCategory, AutoTranslatePhrase, AutoTranslateTable};
+
+    fn phrase(id: u8, text: &str, alt_text: &str) -> AutoTranslatePhrase {
+        AutoTranslatePhrase {
+            id,
+            text: text.to_string(),
+            alt_text: alt_text.to_string(),
+        }
+    }
+
+    #[test]
+    pub fn roundtrip() {
+        let table = AutoTranslateTable {
+            language: 2,
+            categories: vec![
+                AutoTranslateCategory {
+                    id: 1,
+                    name: "Greetings".to_string(),
+                    phrases: vec![phrase(1, "Hello!", ""), phrase(2, "Goodbye.", "Bye")],
+                },
+                AutoTranslateCategory {
+                    id: 2,
+                    name: "Questions".to_string(),
+                    phrases: vec![phrase(1, "Where are you?", "")],
+                },
+            ],
+        };
+
+        let bytes = table.to_bytes().unwrap();
+        assert_eq!(
+            &bytes[..13],
+            &[0x02, 0x02, 0x01, 0x00, 0x0A, b'G', b'r', b'e', b'e', b't', b'i', b'n', b'g']
+        );
+
+        let res = AutoTranslateTable::from_bytes_checked(&bytes).unwrap();
+        assert_eq!(res.language, 2);
+        assert_eq!(res.categories, table.categories);
+
+        let phrases = res.phrases().collect::<Vec<_>>();
+        assert_eq!(phrases.len(), 3);
+        assert_eq!(
+            phrases[1],
+            (
+                AutoTranslateId {
+                    language: 2,
+                    category: 1,
+                    phrase: 2
+                },
+                "Goodbye.".to_string()
+            )
+        );
+    }
+}
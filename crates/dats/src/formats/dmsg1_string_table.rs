@@ -1,14 +1,26 @@
 use anyhow::{anyhow, Result};
 use common::{byte_walker::ByteWalker, expect, writing_byte_walker::WritingByteWalker};
-use encoding::decoder::Decoder;
+use encoding::{decoder::Decoder, encoder::Encoder};
+use serde_derive::{Deserialize, Serialize};
 
 use crate::dat_format::DatFormat;
 
+#[derive(Debug)]
+struct DmsgStringTable1Headers {
+    entry_count: u32,
+    entry_bytes: u32,
+    data_bytes: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct DmsgStringTable1 {
-    entries: Vec<DmsgStringTable1Entry>,
+    pub entries: Vec<DmsgStringTable1Entry>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DmsgStringTable1Entry {
+    pub string: String,
     unknown1: u32,
     unknown2: u16,
     unknown3: u32,
@@ -18,27 +30,79 @@ pub struct DmsgStringTable1Entry {
     unknown7: u32,
     unknown8: u16,
     unknown9: u16,
-    string: String,
 }
 
 const HEADER_SIZE: u32 = 0x38;
 const ENTRY_SIZE: u32 = 0x24;
 
 impl DmsgStringTable1Entry {
-    pub fn write<T: WritingByteWalker>(
+    fn parse<T: ByteWalker>(
+        walker: &mut T,
+        data_start: u32,
+        data_bytes: u32,
+        idx: u32,
+    ) -> Result<DmsgStringTable1Entry> {
+        walker.goto(HEADER_SIZE + ENTRY_SIZE * idx);
+
+        let data_offset: u32 = walker.step()?;
+
+        let unknown1: u32 = walker.step()?;
+
+        let string_len: i16 = walker.step()?;
+
+        let unknown2: u16 = walker.step()?;
+        let unknown3: u32 = walker.step()?;
+        let unknown4: u32 = walker.step()?;
+        let unknown5: u32 = walker.step()?;
+        let unknown6: u32 = walker.step()?;
+        let unknown7: u32 = walker.step()?;
+        let unknown8: u16 = walker.step()?;
+        let unknown9: u16 = walker.step()?;
+
+        if string_len < 0 || (data_offset + string_len as u32) > data_bytes {
+            return Err(anyhow!(
+                "Invalid offset ({data_offset}) or string length ({string_len}) to fit into {data_bytes} bytes.",
+            ));
+        }
+
+        walker.goto(data_start + data_offset);
+        Ok(DmsgStringTable1Entry {
+            string: Decoder::decode_simple(walker.take_bytes(string_len as usize)?)?,
+            unknown1,
+            unknown2,
+            unknown3,
+            unknown4,
+            unknown5,
+            unknown6,
+            unknown7,
+            unknown8,
+            unknown9,
+        })
+    }
+
+    fn write<T: WritingByteWalker>(
         &self,
         walker: &mut T,
+        data_start: u32,
         current_data_offset: &mut u32,
         idx: u32,
-    ) {
+    ) -> Result<()> {
+        let encoded_string = Encoder::encode_simple(&self.string)?;
+        let string_len = encoded_string.len();
+        if string_len > i16::MAX as usize {
+            return Err(anyhow!(
+                "String for entry {idx} is too long ({string_len} bytes): {}",
+                self.string
+            ));
+        }
+
         walker.goto(HEADER_SIZE + ENTRY_SIZE * idx);
 
-        walker.write(current_data_offset.clone());
+        walker.write(*current_data_offset);
 
         walker.write(self.unknown1);
 
-        let string_len = self.string.len() as u32;
-        walker.write(string_len);
+        walker.write(string_len as u16);
 
         walker.write(self.unknown2);
         walker.write(self.unknown3);
@@ -49,101 +113,65 @@ impl DmsgStringTable1Entry {
         walker.write(self.unknown8);
         walker.write(self.unknown9);
 
-        walker.goto(*current_data_offset);
+        walker.goto(data_start + *current_data_offset);
+        walker.write_bytes(&encoded_string);
 
-        walker.write_str(&self.string);
+        *current_data_offset += string_len as u32;
 
-        *current_data_offset += string_len;
+        Ok(())
     }
 }
 
-fn parse_dmsg_string_table_entry<T: ByteWalker>(
-    walker: &mut T,
-    data_start: u32,
-    data_bytes: u32,
-    idx: u32,
-) -> Result<DmsgStringTable1Entry> {
-    walker.goto(HEADER_SIZE + ENTRY_SIZE * idx);
-
-    let data_offset: u32 = walker.step()?;
-
-    let unknown1: u32 = walker.step()?;
-
-    let string_len: i16 = walker.step()?;
+impl DmsgStringTable1 {
+    fn parse_headers<T: ByteWalker>(walker: &mut T) -> Result<DmsgStringTable1Headers> {
+        walker.expect_utf8_str("d_msg")?;
+        walker.expect_utf8_str("\0\0\0")?;
+        walker.expect(1u16)?;
+        walker.expect(0u32)?;
+        walker.expect(2u16)?;
+        walker.expect(3u32)?;
 
-    let unknown2: u16 = walker.step()?;
-    let unknown3: u32 = walker.step()?;
-    let unknown4: u32 = walker.step()?;
-    let unknown5: u32 = walker.step()?;
-    let unknown6: u32 = walker.step()?;
-    let unknown7: u32 = walker.step()?;
-    let unknown8: u16 = walker.step()?;
-    let unknown9: u16 = walker.step()?;
+        let entry_count: u32 = walker.step()?;
+        walker.expect(1u32)?;
 
-    if string_len < 0 || (data_offset + string_len as u32) > data_bytes {
-        return Err(anyhow!(
-            "Invalid offset ({data_offset}) or string length ({string_len}) to fit into {data_bytes} bytes.",
-        ));
-    }
-
-    walker.goto(data_start + data_offset);
-    Ok(DmsgStringTable1Entry {
-        unknown1,
-        unknown2,
-        unknown3,
-        unknown4,
-        unknown5,
-        unknown6,
-        unknown7,
-        unknown8,
-        unknown9,
-        string: Decoder::decode_simple(walker.take_bytes(string_len as usize)?)?,
-    })
-}
+        let bytes_len = walker.len() as u32;
+        walker.expect(bytes_len)?;
 
-fn parse_dmsg_string_table<T: ByteWalker>(walker: &mut T) -> Result<DmsgStringTable1> {
-    walker.expect_utf8_str("d_msg")?;
-    walker.expect_utf8_str("\0\0\0")?;
-    walker.expect(1u16)?;
-    walker.expect(0u32)?;
-    walker.expect(2u16)?;
-    walker.expect(3u32)?;
+        let header_bytes: u32 = walker.step()?;
+        expect(HEADER_SIZE, header_bytes)?;
 
-    let entry_count: u32 = walker.step()?;
-    walker.expect(1u32)?;
+        let entry_bytes: u32 = walker.step()?;
+        expect(ENTRY_SIZE * entry_count, entry_bytes)?;
 
-    let bytes_len = walker.len() as u32;
-    walker.expect(bytes_len)?;
+        let data_bytes: u32 = walker.step()?;
+        expect(bytes_len, HEADER_SIZE + entry_bytes + data_bytes)?;
 
-    let header_bytes: u32 = walker.step()?;
-    expect(HEADER_SIZE, header_bytes)?;
+        walker.expect(0u32)?;
+        walker.expect(0u32)?;
+        walker.expect(0u32)?;
 
-    let entry_bytes: u32 = walker.step()?;
-    expect(36 * entry_count, entry_bytes)?;
+        Ok(DmsgStringTable1Headers {
+            entry_count,
+            entry_bytes,
+            data_bytes,
+        })
+    }
 
-    let data_bytes: u32 = walker.step()?;
-    expect(bytes_len, HEADER_SIZE + entry_bytes + data_bytes)?;
+    pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<DmsgStringTable1> {
+        let headers = Self::parse_headers(walker)?;
 
-    walker.expect(0u32)?;
-    walker.expect(0u32)?;
-    walker.expect(0u32)?;
+        let data_start = HEADER_SIZE + headers.entry_bytes;
 
-    let data_start = HEADER_SIZE + entry_bytes;
+        let entries = (0..headers.entry_count)
+            .map(|idx| {
+                DmsgStringTable1Entry::parse(walker, data_start, headers.data_bytes, idx)
+                    .map_err(|err| anyhow!("Failed to parse entry {idx}: {err}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-    let mut data = vec![];
-    for idx in 0..entry_count {
-        match parse_dmsg_string_table_entry(walker, data_start, data_bytes, idx) {
-            Ok(block) => data.push(block),
-            Err(err) => {
-                return Err(anyhow!("Failed to parse block: {err}"));
-            }
-        }
+        Ok(DmsgStringTable1 { entries })
     }
 
-    Ok(DmsgStringTable1 { entries: data })
-}
-
-impl DatFormat for DmsgStringTable1 {
     fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
         walker.write_str("d_msg");
         walker.skip(3);
@@ -166,17 +194,19 @@ impl DatFormat for DmsgStringTable1 {
         let data_bytes_len_offset = walker.offset() as u32;
         walker.skip(4); // Reserved for data bytes len
 
-        walker.skip(4 * 3);
+        walker.write(0u32);
+        walker.write(0u32);
+        walker.write(0u32);
 
-        // Write all entries
+        // Write all entries, where the data offsets are relative to the start of the data
         let data_start = HEADER_SIZE + entry_bytes_len;
-        let mut current_data_offset = data_start;
+        let mut current_data_offset = 0;
         for (idx, entry) in self.entries.iter().enumerate() {
-            entry.write(walker, &mut current_data_offset, idx as u32);
+            entry.write(walker, data_start, &mut current_data_offset, idx as u32)?;
         }
 
         // Go back and write in length of data and full DAT
-        let data_bytes_len = current_data_offset - data_start;
+        let data_bytes_len = current_data_offset;
         walker.goto(data_bytes_len_offset);
         walker.write(data_bytes_len);
 
@@ -184,14 +214,64 @@ impl DatFormat for DmsgStringTable1 {
         walker.goto(all_bytes_len_offset);
         walker.write(bytes_len);
 
+        walker.goto(bytes_len);
+
         Ok(())
     }
+}
+
+impl DatFormat for DmsgStringTable1 {
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        self.write(walker)
+    }
 
     fn from<T: ByteWalker>(walker: &mut T) -> Result<Self> {
-        parse_dmsg_string_table(walker)
+        DmsgStringTable1::parse(walker)
+    }
+
+    fn check_type<T: ByteWalker>(walker: &mut T) -> Result<()> {
+        DmsgStringTable1::parse_headers(walker)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dat_format::DatFormat;
+
+    use super::{DmsgStringTable1, DmsgStringTable1Entry};
+
+    fn entry(string: &str, unknown1: u32) -> DmsgStringTable1Entry {
+        DmsgStringTable1Entry {
+            string: string.to_string(),
+            unknown1,
+            unknown2: 0,
+            unknown3: 1,
+            unknown4: 2,
+            unknown5: 3,
+            unknown6: 4,
+            unknown7: 5,
+            unknown8: 6,
+            unknown9: 7,
+        }
     }
 
-    fn check_type<T: ByteWalker>(_walker: &mut T) -> Result<()> {
-        Err(anyhow!("Not yet implemented"))
+    #[test]
+    pub fn roundtrip() {
+        let table = DmsgStringTable1 {
+            entries: vec![entry("First string", 10), entry("", 11), entry("Third", 12)],
+        };
+
+        let bytes = table.to_bytes().unwrap();
+        DmsgStringTable1::check_type(&mut common::byte_walker::BufferedByteWalker::on(&bytes))
+            .unwrap();
+
+        let res = DmsgStringTable1::from_bytes_checked(&bytes).unwrap();
+
+        assert_eq!(res.entries.len(), 3);
+        assert_eq!(res.entries[0].string, "First string");
+        assert_eq!(res.entries[1].string, "");
+        assert_eq!(res.entries[2].string, "Third");
+        assert_eq!(res.entries[2].unknown1, 12);
     }
 }
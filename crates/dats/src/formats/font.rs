@@ -0,0 +1,308 @@
+use anyhow::{anyhow, Result};
+use common::{byte_walker::ByteWalker, writing_byte_walker::WritingByteWalker};
+use encoding::{conversion_tables::ConversionTable, decoder::Decoder};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    dat_format::DatFormat,
+    external_files::ExternalFiles,
+    image::{XiImage, XiImageFormat, XiImageType},
+    serde_hex,
+};
+
+const HEADER_SIZE: usize = 0x10;
+const GLYPH_ENTRY_SIZE: usize = 4;
+
+/// Font DATs are padded to a multiple of this size.
+const FONT_ALIGNMENT: usize = 0x10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Glyph {
+    /// The encoded bytes of the character, as mapped by the conversion tables.
+    #[serde(with = "serde_hex")]
+    pub bytes: Vec<u8>,
+
+    /// The decoded character. It's only for reading, and is not used when writing the DAT.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub character: Option<String>,
+
+    /// Advance width in pixels.
+    pub width: u8,
+}
+
+impl Glyph {
+    fn parse<T: ByteWalker>(walker: &mut T) -> Result<Glyph> {
+        let code = walker.step::<u16>()?;
+        let width = walker.step::<u8>()?;
+        walker.expect_msg::<u8>(0, "Glyph padding")?;
+
+        let bytes = match code {
+            0x00..=0xFF => vec![code as u8],
+            _ => code.to_be_bytes().to_vec(),
+        };
+
+        Ok(Glyph {
+            character: Decoder::decode_simple(&bytes).ok(),
+            bytes,
+            width,
+        })
+    }
+
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        let code = match self.bytes[..] {
+            [byte] => byte as u16,
+            [first, second] => u16::from_be_bytes([first, second]),
+            _ => {
+                return Err(anyhow!(
+                    "Glyphs must be for 1 or 2 bytes, got {:02X?}.",
+                    self.bytes
+                ))
+            }
+        };
+
+        walker.write(code);
+        walker.write(self.width);
+        walker.write(0u8);
+
+        Ok(())
+    }
+}
+
+/// A bitmap font, with glyphs stored as cells of an atlas image in the order of the glyph list.
+///
+/// The header and glyph entry layout haven't been checked against a retail font DAT yet,
+/// so the widths are only as accurate as that layout.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FontTable {
+    pub cell_width: u16,
+    pub cell_height: u16,
+    pub glyphs: Vec<Glyph>,
+
+    /// Reference to the atlas PNG, which is exported next to the raw data file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    atlas: Option<String>,
+
+    #[serde(skip)]
+    atlas_bytes: Vec<u8>,
+}
+
+impl FontTable {
+    fn parse_header<T: ByteWalker>(walker: &mut T) -> Result<(usize, u16, u16, usize)> {
+        let glyph_count = walker.step::<u32>()? as usize;
+        let cell_width = walker.step::<u16>()?;
+        let cell_height = walker.step::<u16>()?;
+        let atlas_size = walker.step::<u32>()? as usize;
+        walker.expect_msg::<u32>(0, "Reserved header bytes")?;
+
+        // The glyphs and the atlas are the only content, so the DAT size follows from them.
+        let content_size = HEADER_SIZE + glyph_count * GLYPH_ENTRY_SIZE + atlas_size;
+        if content_size.next_multiple_of(FONT_ALIGNMENT) != walker.len() {
+            return Err(anyhow!(
+                "{} glyphs and an atlas of {} bytes do not match a DAT of {} bytes.",
+                glyph_count,
+                atlas_size,
+                walker.len()
+            ));
+        }
+
+        Ok((glyph_count, cell_width, cell_height, atlas_size))
+    }
+
+    pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        let (glyph_count, cell_width, cell_height, atlas_size) = Self::parse_header(walker)?;
+
+        let glyphs = (0..glyph_count)
+            .map(|_| Glyph::parse(walker))
+            .collect::<Result<Vec<_>>>()?;
+        let atlas_bytes = walker.take_bytes(atlas_size)?.to_vec();
+
+        let padding = walker.remaining();
+        walker.expect_n_msg::<u8>(0, padding, "Padding after font atlas")?;
+
+        Ok(FontTable {
+            cell_width,
+            cell_height,
+            glyphs,
+            atlas: None,
+            atlas_bytes,
+        })
+    }
+
+    pub fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        walker.write(self.glyphs.len() as u32);
+        walker.write(self.cell_width);
+        walker.write(self.cell_height);
+        walker.write(self.atlas_bytes.len() as u32);
+        walker.write(0u32);
+
+        for glyph in &self.glyphs {
+            glyph.write(walker)?;
+        }
+        walker.write_bytes(&self.atlas_bytes);
+
+        walker.set_size(walker.offset().next_multiple_of(FONT_ALIGNMENT));
+
+        Ok(())
+    }
+
+    fn glyph_index(&self, bytes: &[u8]) -> Option<usize> {
+        self.glyphs.iter().position(|glyph| glyph.bytes == bytes)
+    }
+
+    /// The advance width of the character with the given encoded bytes.
+    pub fn glyph_width(&self, bytes: &[u8]) -> Option<u8> {
+        self.glyph_index(bytes).map(|idx| self.glyphs[idx].width)
+    }
+
+    /// The sum of the glyph advance widths of an encoded string, like the result of
+    /// `Encoder::encode_simple`. Bytes without a glyph, like control codes, don't add to the width.
+    /// This doesn't account for kerning or spacing the client may add.
+    pub fn measure(&self, encoded: &[u8]) -> u32 {
+        let mut width = 0;
+        let mut idx = 0;
+        while idx < encoded.len() {
+            let len = if ConversionTable::is_lead_byte(encoded[idx]) {
+                2.min(encoded.len() - idx)
+            } else {
+                1
+            };
+
+            width += self.glyph_width(&encoded[idx..idx + len]).unwrap_or(0) as u32;
+            idx += len;
+        }
+
+        width
+    }
+
+    /// The RGBA pixels of the cell of a character in the atlas, row by row.
+    pub fn glyph_pixels(&self, bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+        let Some(idx) = self.glyph_index(bytes) else {
+            return Ok(None);
+        };
+
+        let atlas = XiImage::from_bytes(&self.atlas_bytes)?;
+        let (cell_width, cell_height) = (self.cell_width as usize, self.cell_height as usize);
+        let columns = atlas.width() as usize / cell_width.max(1);
+        let rows = atlas.height() as usize / cell_height.max(1);
+        if idx >= columns * rows {
+            return Err(anyhow!(
+                "Glyph {} is outside of the {}x{} atlas.",
+                idx,
+                atlas.width(),
+                atlas.height()
+            ));
+        }
+
+        let (left, top) = ((idx % columns) * cell_width, (idx / columns) * cell_height);
+        let row_bytes = atlas.width() as usize * 4;
+        Ok(Some(
+            (top..top + cell_height)
+                .flat_map(|y| {
+                    let row_start = y * row_bytes + left * 4;
+                    &atlas.pixels[row_start..row_start + cell_width * 4]
+                })
+                .copied()
+                .collect(),
+        ))
+    }
+}
+
+impl DatFormat for FontTable {
+    fn from<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        FontTable::parse(walker)
+    }
+
+    fn check_type<T: ByteWalker>(walker: &mut T) -> Result<()> {
+        let (glyph_count, ..) = FontTable::parse_header(walker)?;
+        walker.skip(glyph_count * GLYPH_ENTRY_SIZE);
+        XiImageType::from(walker.step::<u8>()?)?;
+
+        Ok(())
+    }
+
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        self.write(walker)
+    }
+
+    fn export_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        let png_bytes = XiImage::bytes_to_png(&self.atlas_bytes)
+            .map_err(|err| anyhow!("Failed to export font atlas: {}", err))?;
+        self.atlas = Some(files.write("atlas.png", &png_bytes)?);
+
+        Ok(())
+    }
+
+    fn import_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        let atlas = self
+            .atlas
+            .as_ref()
+            .ok_or(anyhow!("Missing reference to the font atlas."))?;
+
+        let template = XiImage::template(XiImageFormat::BitmapA, "font    ", "font    ", 32);
+        self.atlas_bytes = XiImage::png_to_bytes(&files.read(atlas)?, &template, usize::MAX)
+            .map_err(|err| anyhow!("Failed to import font atlas: {}", err))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding::encoder::Encoder;
+
+    use crate::{
+        dat_format::DatFormat,
+        image::{XiImage, XiImageFormat},
+    };
+
+    use super::{FontTable, Glyph};
+
+    fn glyph(bytes: &[u8], width: u8) -> Glyph {
+        Glyph {
+            bytes: bytes.to_vec(),
+            character: None,
+            width,
+        }
+    }
+
+    #[test]
+    pub fn font() {
+        // A 4x2 atlas with two 2x2 cells, one white and one red.
+        let white = [0xFF, 0xFF, 0xFF, 0xFF];
+        let red = [0xFF, 0x00, 0x00, 0xFF];
+        let pixels = [white, white, red, red, white, white, red, red].concat();
+        let atlas_bytes = XiImage::template(XiImageFormat::BitmapA, "font    ", "font    ", 32)
+            .with_pixels(4, 2, pixels)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+
+        let font = FontTable {
+            cell_width: 2,
+            cell_height: 2,
+            glyphs: vec![glyph(b"A", 7), glyph(&[0x82, 0xA0], 12)],
+            atlas: None,
+            atlas_bytes,
+        };
+
+        let bytes = font.to_bytes().unwrap();
+        let res = FontTable::from_bytes_checked(&bytes).unwrap();
+        assert_eq!(res.glyphs[0].character.as_deref(), Some("A"));
+        assert_eq!(res.glyphs[1].bytes, vec![0x82, 0xA0]);
+        assert_eq!(res.to_bytes().unwrap(), bytes);
+
+        // Nothing but padding may follow the atlas.
+        assert!(FontTable::from_bytes_checked(&[bytes.as_slice(), &[0; 0x10]].concat()).is_err());
+
+        assert_eq!(
+            res.glyph_pixels(&[0x82, 0xA0]).unwrap(),
+            Some(red.repeat(4))
+        );
+        assert_eq!(res.glyph_pixels(b"B").unwrap(), None);
+
+        let encoded = Encoder::encode_simple("AあA").unwrap();
+        assert_eq!(encoded, vec![b'A', 0x82, 0xA0, b'A']);
+        assert_eq!(res.measure(&encoded), 7 + 12 + 7);
+    }
+}
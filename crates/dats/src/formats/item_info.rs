@@ -10,9 +10,10 @@ use serde_derive::{Deserialize, Serialize};
 
 use crate::{
     dat_format::DatFormat,
-    enums::{Element, EnglishArticle, ItemType, PuppetSlot, SkillType},
+    enums::{Element, EnglishArticle, FrenchGender, GermanGender, ItemType, PuppetSlot, SkillType},
+    external_files::ExternalFiles,
     flags::{EquipmentSlot, ItemFlag, JobFlag, Race, ValidTargets},
-    serde_base64,
+    image::{XiImage, XiImageFormat},
     utils::{get_nibble, rotate_all},
 };
 
@@ -66,14 +67,42 @@ pub struct ItemInfo {
     #[serde(default)]
     monipulator: Option<MonipulatorData>,
 
-    #[serde(with = "serde_base64")]
+    /// Reference to the icon, which is exported next to the raw data file as a PNG,
+    /// or as raw bytes if it can't be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    icon: Option<String>,
+
+    #[serde(skip)]
     icon_bytes: Vec<u8>,
 }
 
+// Untagged variants are tried in order, and ignore fields they don't have,
+// so variants with more fields must come first.
 #[derive(Debug, Serialize, Deserialize)]
 pub enum ItemStrings {
+    /// German names have a gender, and more forms of the name for the grammatical cases,
+    /// which are not all identified yet.
     #[serde(untagged)]
-    Name { name: String },
+    German {
+        name: String,
+        gender: GermanGender,
+        singular_name: String,
+        plural_name: String,
+        unknown_names: Vec<String>,
+        description: String,
+    },
+
+    /// French names have a gender, which picks the article they are used with.
+    #[serde(untagged)]
+    French {
+        name: String,
+        gender: FrenchGender,
+        singular_name: String,
+        plural_name: String,
+        unknown_name: String,
+        description: String,
+    },
 
     #[serde(untagged)]
     English {
@@ -83,8 +112,24 @@ pub enum ItemStrings {
         plural_name: String,
         description: String,
     },
+
+    /// Japanese names have no articles or plural forms.
+    #[serde(untagged)]
+    Japanese { name: String, description: String },
+
+    #[serde(untagged)]
+    Name { name: String },
 }
 
+/// French items have the name, the gender, the singular and plural names,
+/// an unknown name, and the description.
+const FRENCH_STRING_COUNT: u32 = 6;
+
+/// German items have the most strings: the name, the gender, the singular and plural names,
+/// the unknown names, and the description.
+const GERMAN_STRING_COUNT: u32 = 9;
+const GERMAN_UNKNOWN_NAME_COUNT: usize = 4;
+
 #[derive(Debug, Clone)]
 pub enum ItemStringContent {
     Number(u32),
@@ -253,6 +298,7 @@ pub struct UsableItemData {
     unknown3: u32,
 }
 
+/// Placeholder for the currency record, whose layout hasn't been checked against a retail DAT.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CurrencyData {
     unknown1: u16,
@@ -264,10 +310,93 @@ pub struct SlipData {
     unknowns: [u32; 17],
 }
 
+/// Monipulator records don't share the fields of other items, and their values are not
+/// identified yet.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MonipulatorData {
     unknown1: u16,
-    unknowns: [u32; 24],
+    unknown2: u16,
+    unknown3: u16,
+    unknown4: u16,
+    unknown5: u16,
+    unknown6: u16,
+
+    /// 16 slots of two values each. Unused slots at the end are left out.
+    unknown_slots: Vec<[u16; 2]>,
+
+    unknowns1: [u16; 10],
+    unknowns2: [u32; 3],
+}
+
+const MONIPULATOR_SLOT_COUNT: usize = 16;
+
+impl MonipulatorData {
+    fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        let unknown1 = walker.step::<u16>()?;
+        let unknown2 = walker.step::<u16>()?;
+        let unknown3 = walker.step::<u16>()?;
+        let unknown4 = walker.step::<u16>()?;
+        let unknown5 = walker.step::<u16>()?;
+        let unknown6 = walker.step::<u16>()?;
+
+        let mut unknown_slots = (0..MONIPULATOR_SLOT_COUNT)
+            .map(|_| Ok([walker.step::<u16>()?, walker.step::<u16>()?]))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Only keep the used slots, the rest are written back as zeroes.
+        while unknown_slots.last() == Some(&[0, 0]) {
+            unknown_slots.pop();
+        }
+
+        let mut unknowns1 = [0; 10];
+        for unknown in &mut unknowns1 {
+            *unknown = walker.step::<u16>()?;
+        }
+
+        Ok(MonipulatorData {
+            unknown1,
+            unknown2,
+            unknown3,
+            unknown4,
+            unknown5,
+            unknown6,
+            unknown_slots,
+            unknowns1,
+            unknowns2: [walker.step()?, walker.step()?, walker.step()?],
+        })
+    }
+
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        if self.unknown_slots.len() > MONIPULATOR_SLOT_COUNT {
+            return Err(anyhow!(
+                "Monipulator can at most have {} slots, but got {}.",
+                MONIPULATOR_SLOT_COUNT,
+                self.unknown_slots.len()
+            ));
+        }
+
+        walker.write(self.unknown1);
+        walker.write(self.unknown2);
+        walker.write(self.unknown3);
+        walker.write(self.unknown4);
+        walker.write(self.unknown5);
+        walker.write(self.unknown6);
+
+        for idx in 0..MONIPULATOR_SLOT_COUNT {
+            let slot = self.unknown_slots.get(idx).copied().unwrap_or_default();
+            walker.write(slot[0]);
+            walker.write(slot[1]);
+        }
+
+        for unknown in self.unknowns1 {
+            walker.write(unknown);
+        }
+        for unknown in self.unknowns2 {
+            walker.write(unknown);
+        }
+
+        Ok(())
+    }
 }
 
 impl ItemInfo {
@@ -295,15 +424,18 @@ impl ItemInfo {
         item_info.id = data_walker.step::<u32>()?;
         let item_category = ItemCategory::from_id(item_info.id);
 
-        // TODO: Monipulators seems to have a totally different structure than other items,
-        //       since the values it gets for the following are non-sensical.
-
-        item_info.flags = ItemFlag::from_bits(data_walker.step::<u16>()?).unwrap_or_default();
-        item_info.stack_size = data_walker.step::<u16>()?;
-        item_info.item_type = ItemType::from(data_walker.step::<u16>()?);
-        item_info.resource_id = data_walker.step::<u16>()?;
-        item_info.valid_targets =
-            ValidTargets::from_bits(data_walker.step::<u16>()?).unwrap_or_default();
+        if item_category == ItemCategory::Monipulator {
+            // Monipulators have a totally different structure than other items,
+            // and do not have the common item fields.
+            item_info.monipulator = Some(MonipulatorData::parse(&mut data_walker)?);
+        } else {
+            item_info.flags = ItemFlag::from_bits(data_walker.step::<u16>()?).unwrap_or_default();
+            item_info.stack_size = data_walker.step::<u16>()?;
+            item_info.item_type = ItemType::from(data_walker.step::<u16>()?);
+            item_info.resource_id = data_walker.step::<u16>()?;
+            item_info.valid_targets =
+                ValidTargets::from_bits(data_walker.step::<u16>()?).unwrap_or_default();
+        }
 
         if item_category == ItemCategory::Armor || item_category == ItemCategory::Weapon {
             let level = data_walker.step::<u16>()?;
@@ -388,16 +520,11 @@ impl ItemInfo {
                 unknown1: data_walker.step::<u16>()?,
                 unknowns: core::array::from_fn(|_| data_walker.step::<u32>().unwrap_or_default()),
             });
-        } else if item_category == ItemCategory::Monipulator {
-            item_info.monipulator = Some(MonipulatorData {
-                unknown1: data_walker.step::<u16>()?,
-                unknowns: core::array::from_fn(|_| data_walker.step::<u32>().unwrap_or_default()),
-            });
         }
 
         // Parse string data
         let content_count = data_walker.step::<u32>()?;
-        if content_count > 9 {
+        if content_count > GERMAN_STRING_COUNT {
             return Err(anyhow!(
                 "Unsupported strings content of length: {}",
                 content_count
@@ -410,12 +537,22 @@ impl ItemInfo {
         }
 
         match content_count {
+            0 => {
+                // Unused entry without any strings
+            }
             1 => {
                 // Just one string name
                 item_info.strings = Some(ItemStrings::Name {
                     name: Self::read_string(&mut data_walker)?,
                 });
             }
+            2 => {
+                // Japanese
+                item_info.strings = Some(ItemStrings::Japanese {
+                    name: Self::read_string(&mut data_walker)?,
+                    description: Self::read_string(&mut data_walker)?,
+                });
+            }
             5 => {
                 // English
                 item_info.strings = Some(ItemStrings::English {
@@ -426,6 +563,30 @@ impl ItemInfo {
                     description: Self::read_string(&mut data_walker)?,
                 });
             }
+            FRENCH_STRING_COUNT => {
+                // French
+                item_info.strings = Some(ItemStrings::French {
+                    name: Self::read_string(&mut data_walker)?,
+                    gender: FrenchGender::from(data_walker.step::<u32>()?),
+                    singular_name: Self::read_string(&mut data_walker)?,
+                    plural_name: Self::read_string(&mut data_walker)?,
+                    unknown_name: Self::read_string(&mut data_walker)?,
+                    description: Self::read_string(&mut data_walker)?,
+                });
+            }
+            GERMAN_STRING_COUNT => {
+                // German
+                item_info.strings = Some(ItemStrings::German {
+                    name: Self::read_string(&mut data_walker)?,
+                    gender: GermanGender::from(data_walker.step::<u32>()?),
+                    singular_name: Self::read_string(&mut data_walker)?,
+                    plural_name: Self::read_string(&mut data_walker)?,
+                    unknown_names: (0..GERMAN_UNKNOWN_NAME_COUNT)
+                        .map(|_| Self::read_string(&mut data_walker))
+                        .collect::<Result<Vec<_>>>()?,
+                    description: Self::read_string(&mut data_walker)?,
+                });
+            }
             count => {
                 return Err(anyhow!("Unsupported string count: {}", count));
             }
@@ -458,13 +619,18 @@ impl ItemInfo {
         let mut walker = VecByteWalker::with_size(0xC00);
 
         walker.write(self.id);
-        walker.write(self.flags.bits());
 
-        // Write item data
-        walker.write(self.stack_size);
-        walker.write::<u16>(self.item_type.into());
-        walker.write(self.resource_id);
-        walker.write(self.valid_targets.bits());
+        if let Some(monipulator) = &self.monipulator {
+            monipulator.write(&mut walker)?;
+        } else {
+            walker.write(self.flags.bits());
+
+            // Write item data
+            walker.write(self.stack_size);
+            walker.write::<u16>(self.item_type.into());
+            walker.write(self.resource_id);
+            walker.write(self.valid_targets.bits());
+        }
 
         if let Some(equipment) = &self.equipment {
             walker.write(equipment.level);
@@ -520,11 +686,6 @@ impl ItemInfo {
             for unknown in slip.unknowns {
                 walker.write(unknown);
             }
-        } else if let Some(monipulator) = &self.monipulator {
-            walker.write(monipulator.unknown1);
-            for unknown in monipulator.unknowns {
-                walker.write(unknown);
-            }
         }
 
         // Write strings
@@ -534,6 +695,51 @@ impl ItemInfo {
             Some(ItemStrings::Name { name }) => {
                 string_content.push(ItemStringContent::from_string(name)?);
             }
+            Some(ItemStrings::German {
+                name,
+                gender,
+                singular_name,
+                plural_name,
+                unknown_names,
+                description,
+            }) => {
+                if unknown_names.len() != GERMAN_UNKNOWN_NAME_COUNT {
+                    return Err(anyhow!(
+                        "Item {} needs {} unknown names, but has {}.",
+                        self.id,
+                        GERMAN_UNKNOWN_NAME_COUNT,
+                        unknown_names.len()
+                    ));
+                }
+
+                string_content.push(ItemStringContent::from_string(name)?);
+                string_content.push(ItemStringContent::from_article(*gender));
+                string_content.push(ItemStringContent::from_string(singular_name)?);
+                string_content.push(ItemStringContent::from_string(plural_name)?);
+                for unknown_name in unknown_names {
+                    string_content.push(ItemStringContent::from_string(unknown_name)?);
+                }
+                string_content.push(ItemStringContent::from_string(description)?);
+            }
+            Some(ItemStrings::French {
+                name,
+                gender,
+                singular_name,
+                plural_name,
+                unknown_name,
+                description,
+            }) => {
+                string_content.push(ItemStringContent::from_string(name)?);
+                string_content.push(ItemStringContent::from_article(*gender));
+                string_content.push(ItemStringContent::from_string(singular_name)?);
+                string_content.push(ItemStringContent::from_string(plural_name)?);
+                string_content.push(ItemStringContent::from_string(unknown_name)?);
+                string_content.push(ItemStringContent::from_string(description)?);
+            }
+            Some(ItemStrings::Japanese { name, description }) => {
+                string_content.push(ItemStringContent::from_string(name)?);
+                string_content.push(ItemStringContent::from_string(description)?);
+            }
             Some(ItemStrings::English {
                 name,
                 article_type,
@@ -584,6 +790,14 @@ impl ItemInfo {
         }
 
         // Write icon bytes
+        if self.icon_bytes.len() > MAX_ICON_SIZE {
+            return Err(anyhow!(
+                "Icon for item {} is {} bytes, but at most {} bytes can fit.",
+                self.id,
+                self.icon_bytes.len(),
+                MAX_ICON_SIZE
+            ));
+        }
         walker.goto(0x280);
         walker.write(self.icon_bytes.len() as u32);
         walker.write_bytes(&self.icon_bytes);
@@ -603,6 +817,9 @@ pub struct ItemInfoTable {
 
 const ENTRY_SIZE: usize = 0xC00;
 
+/// Icons are placed after the item data at 0x280, with a leading size and an ending 0xFF.
+const MAX_ICON_SIZE: usize = ENTRY_SIZE - 0x280 - 4 - 1;
+
 impl ItemInfoTable {
     pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
         if walker.len() % ENTRY_SIZE != 0 {
@@ -648,15 +865,47 @@ impl DatFormat for ItemInfoTable {
     fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
         self.write(walker)
     }
+
+    fn export_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        for item in &mut self.items {
+            if item.icon_bytes.is_empty() {
+                continue;
+            }
+
+            item.icon = Some(files.write_image(&format!("icons/{}", item.id), &item.icon_bytes)?);
+        }
+
+        Ok(())
+    }
+
+    fn import_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        for item in &mut self.items {
+            let Some(icon) = &item.icon else {
+                continue;
+            };
+
+            let template = XiImage::template(XiImageFormat::BitmapA, "menu    ", "item    ", 8);
+            item.icon_bytes = files
+                .read_image(icon, &template, MAX_ICON_SIZE)
+                .map_err(|err| anyhow!("Failed to import icon for item {}: {}", item.id, err))?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
-    use crate::{dat_format::DatFormat, enums::EnglishArticle};
+    use crate::{
+        dat_format::DatFormat,
+        enums::{EnglishArticle, FrenchGender, GermanGender, ItemType},
+    };
 
-    use super::{ItemInfoTable, ItemStrings};
+    use super::{
+        CurrencyData, ItemInfo, ItemInfoTable, ItemStrings, MonipulatorData, UsableItemData,
+    };
 
     #[test]
     pub fn weapons() {
@@ -709,4 +958,164 @@ mod tests {
             panic!("Expected english strings")
         }
     }
+
+    #[test]
+    pub fn currency_roundtrip() {
+        let table = ItemInfoTable {
+            items: vec![ItemInfo {
+                id: 0xFFFF,
+                strings: Some(ItemStrings::English {
+                    name: "Gil".to_string(),
+                    article_type: EnglishArticle::A,
+                    singular_name: "gil".to_string(),
+                    plural_name: "gil".to_string(),
+                    description: "".to_string(),
+                }),
+                item_type: ItemType::Currency,
+                currency: Some(CurrencyData { unknown1: 0x1234 }),
+                ..Default::default()
+            }],
+        };
+
+        let bytes = table.to_bytes().unwrap();
+        let res = ItemInfoTable::from_bytes_checked(&bytes).unwrap();
+
+        assert_eq!(res.items[0].currency.as_ref().unwrap().unknown1, 0x1234);
+        assert_eq!(res.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    pub fn monipulator_roundtrip() {
+        let table = ItemInfoTable {
+            items: vec![ItemInfo {
+                id: 0x7800,
+                strings: Some(ItemStrings::English {
+                    name: "Rabbit".to_string(),
+                    article_type: EnglishArticle::A,
+                    singular_name: "rabbit".to_string(),
+                    plural_name: "rabbits".to_string(),
+                    description: "".to_string(),
+                }),
+                monipulator: Some(MonipulatorData {
+                    unknown1: 1,
+                    unknown2: 2,
+                    unknown3: 3,
+                    unknown4: 4,
+                    unknown5: 5,
+                    unknown6: 6,
+                    unknown_slots: vec![[7, 1], [8, 2]],
+                    unknowns1: [100, 20, 10, 11, 12, 13, 14, 15, 16, 17],
+                    unknowns2: [18, 19, 20],
+                }),
+                ..Default::default()
+            }],
+        };
+
+        let bytes = table.to_bytes().unwrap();
+        let res = ItemInfoTable::from_bytes_checked(&bytes).unwrap();
+
+        let monipulator = res.items[0].monipulator.as_ref().unwrap();
+        assert_eq!(monipulator.unknown_slots, vec![[7, 1], [8, 2]]);
+        assert_eq!(monipulator.unknowns1[8], 16);
+        assert_eq!(monipulator.unknowns2, [18, 19, 20]);
+        assert_eq!(res.to_bytes().unwrap(), bytes);
+    }
+
+    fn strings_roundtrip(strings: ItemStrings) -> ItemStrings {
+        let table = ItemInfoTable {
+            items: vec![ItemInfo {
+                id: 0x1000,
+                strings: Some(strings),
+                item_type: ItemType::Crystal,
+                usable_item: Some(UsableItemData {
+                    activation_time: 0,
+                    unknown1: 0,
+                    unknown2: 0,
+                    unknown3: 0,
+                }),
+                ..Default::default()
+            }],
+        };
+
+        let yaml = serde_yaml::to_string(&table).unwrap();
+        let table: ItemInfoTable = serde_yaml::from_str(&yaml).unwrap();
+
+        let bytes = table.to_bytes().unwrap();
+        let mut res = ItemInfoTable::from_bytes_checked(&bytes).unwrap();
+        assert_eq!(res.to_bytes().unwrap(), bytes);
+
+        res.items.remove(0).strings.unwrap()
+    }
+
+    #[test]
+    pub fn japanese_strings_roundtrip() {
+        let res = strings_roundtrip(ItemStrings::Japanese {
+            name: "ファイアクリスタル".to_string(),
+            description: "炎の力が封じられた\nクリスタル。".to_string(),
+        });
+
+        if let ItemStrings::Japanese { name, description } = res {
+            assert_eq!(name, "ファイアクリスタル");
+            assert_eq!(description, "炎の力が封じられた\nクリスタル。");
+        } else {
+            panic!("Expected japanese strings")
+        }
+    }
+
+    #[test]
+    pub fn french_strings_roundtrip() {
+        let res = strings_roundtrip(ItemStrings::French {
+            name: "Cristal de feu".to_string(),
+            gender: FrenchGender::Masculine,
+            singular_name: "cristal de feu".to_string(),
+            plural_name: "cristaux de feu".to_string(),
+            unknown_name: "".to_string(),
+            description: "Un cristal qui renferme\nla puissance du feu.".to_string(),
+        });
+
+        if let ItemStrings::French {
+            gender,
+            plural_name,
+            description,
+            ..
+        } = res
+        {
+            assert_eq!(gender, FrenchGender::Masculine);
+            assert_eq!(plural_name, "cristaux de feu");
+            assert_eq!(description, "Un cristal qui renferme\nla puissance du feu.");
+        } else {
+            panic!("Expected french strings")
+        }
+    }
+
+    #[test]
+    pub fn german_strings_roundtrip() {
+        let res = strings_roundtrip(ItemStrings::German {
+            name: "Feuerkristall".to_string(),
+            gender: GermanGender::Neuter,
+            singular_name: "Feuerkristall".to_string(),
+            plural_name: "Feuerkristalle".to_string(),
+            unknown_names: vec![
+                "Feuerkristalls".to_string(),
+                "Feuerkristall".to_string(),
+                "Feuerkristallen".to_string(),
+                "Feuerkristalle".to_string(),
+            ],
+            description: "Ein Kristall mit der\nKraft des Feuers.".to_string(),
+        });
+
+        if let ItemStrings::German {
+            gender,
+            unknown_names,
+            description,
+            ..
+        } = res
+        {
+            assert_eq!(gender, GermanGender::Neuter);
+            assert_eq!(unknown_names[2], "Feuerkristallen");
+            assert_eq!(description, "Ein Kristall mit der\nKraft des Feuers.");
+        } else {
+            panic!("Expected german strings")
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 use crate::{
+    chunk::{Chunk, ChunkDecoders, ChunkHeader, NoPayload},
     enums::{Element, JobEnum, MagicType, SkillType},
     serde_base64, serde_hex,
     utils::{decode_data_block_masked, encode_data_block_masked},
@@ -14,17 +15,25 @@ use common::{
 };
 use serde_derive::{Deserialize, Serialize};
 
-use crate::{dat_format::DatFormat, enums::AbilityType, flags::ValidTargets};
+use crate::{
+    dat_format::DatFormat,
+    enums::AbilityType,
+    flags::{JobFlag, ValidTargets},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "entries")]
 pub enum Section {
+    // The mnc2, mon_ and levc sections aren't identified yet, so they are kept as-is.
     Mnc2(#[serde(with = "serde_base64")] Vec<u8>),
     Mon_(#[serde(with = "serde_base64")] Vec<u8>),
     Levc(#[serde(with = "serde_base64")] Vec<u8>),
     Comm(Vec<AbilityInfo>),
     Mgc_(Vec<MagicInfo>),
 
+    /// Sections that aren't known yet are kept as-is.
+    Unknown(Chunk<NoPayload>),
+
     End,
 }
 
@@ -64,14 +73,18 @@ pub trait SectionInfo: Sized {
 
 impl Section {
     pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<Section> {
-        let section_code = String::from_utf8(walker.take_bytes(4)?.to_vec())?;
-        let size_info = walker.step::<u32>()?;
-        let section_size = ((size_info & 0xFFFFFF80) >> 3) - 16;
-        let unknown_section_info = (size_info & 0x7F) as u8;
+        let header = ChunkHeader::parse(walker)?;
+        let section_size = header.content_size;
+        let unknown_section_info = header.flags;
 
-        walker.expect_n_msg::<u8>(0, 8, "Padding after section size info")?;
+        if header.reserved != [0; 8] {
+            return Err(anyhow!(
+                "Expected padding after section size info, found {:?}.",
+                header.reserved
+            ));
+        }
 
-        let section = match section_code.as_str() {
+        let section = match header.code.as_str() {
             "mnc2" => Section::Mnc2(walker.take_bytes(section_size as usize)?.to_vec()),
             "mon_" => Section::Mon_(walker.take_bytes(section_size as usize)?.to_vec()),
             "levc" => Section::Levc(walker.take_bytes(section_size as usize)?.to_vec()),
@@ -79,7 +92,11 @@ impl Section {
             "mgc_" => Section::Mgc_(MagicInfo::parse_all(walker, section_size)?),
             "end\0" => Section::End,
             _ => {
-                return Err(anyhow!("Unknown section code: {}", section_code));
+                return Ok(Section::Unknown(Chunk::parse_content(
+                    header,
+                    walker,
+                    &ChunkDecoders::new(),
+                )?))
             }
         };
 
@@ -101,6 +118,7 @@ impl Section {
             Section::Levc(_) => 4,
             Section::Comm(_) => 83,
             Section::Mgc_(_) => 73,
+            Section::Unknown(chunk) => chunk.flags,
             Section::End => 0,
         }
     }
@@ -115,19 +133,19 @@ impl Section {
                 walker.write_str("mnc2");
                 walker.write(self.get_section_info(bytes.len() as u32));
                 walker.skip(8);
-                walker.write_bytes(&bytes);
+                walker.write_bytes(bytes);
             }
             Section::Mon_(bytes) => {
                 walker.write_str("mon_");
                 walker.write(self.get_section_info(bytes.len() as u32));
                 walker.skip(8);
-                walker.write_bytes(&bytes);
+                walker.write_bytes(bytes);
             }
             Section::Levc(bytes) => {
                 walker.write_str("levc");
                 walker.write(self.get_section_info(bytes.len() as u32));
                 walker.skip(8);
-                walker.write_bytes(&bytes);
+                walker.write_bytes(bytes);
             }
             Section::Comm(comm) => {
                 walker.write_str("comm");
@@ -144,6 +162,8 @@ impl Section {
                 walker.write_at(size_info_offset, self.get_section_info(content_len));
             }
 
+            Section::Unknown(chunk) => chunk.write(walker)?,
+
             Section::End => unreachable!(),
         };
 
@@ -158,9 +178,18 @@ pub struct AbilityInfo {
     icon_id: u8,
     mp_cost: u16,
     unknown1: u16,
+    /// The recast id, shared by abilities that share a timer.
     shared_timer_id: u16,
     valid_targets: ValidTargets,
     tp_cost: i16,
+    unknown2: u8,
+    /// 0xFF for abilities that are not learned by level.
+    required_level: u8,
+    range: u8,
+    /// Radius of the area of effect, 0 for single target abilities.
+    area_range: u8,
+    area_type: u8,
+    unknown3: u8,
 
     #[serde(with = "serde_hex")]
     unknowns: Vec<u8>,
@@ -186,6 +215,12 @@ impl SectionInfo for AbilityInfo {
             shared_timer_id: data_walker.step::<u16>()?,
             valid_targets: ValidTargets::from_bits(data_walker.step::<u16>()?).unwrap_or_default(),
             tp_cost: data_walker.step::<i16>()?,
+            unknown2: data_walker.step::<u8>()?,
+            required_level: data_walker.step::<u8>()?,
+            range: data_walker.step::<u8>()?,
+            area_range: data_walker.step::<u8>()?,
+            area_type: data_walker.step::<u8>()?,
+            unknown3: data_walker.step::<u8>()?,
             unknowns: data_walker
                 .take_bytes(data_walker.remaining() - 1)?
                 .to_vec(),
@@ -207,6 +242,12 @@ impl SectionInfo for AbilityInfo {
         data_walker.write(self.shared_timer_id);
         data_walker.write(self.valid_targets.bits());
         data_walker.write(self.tp_cost);
+        data_walker.write(self.unknown2);
+        data_walker.write(self.required_level);
+        data_walker.write(self.range);
+        data_walker.write(self.area_range);
+        data_walker.write(self.area_type);
+        data_walker.write(self.unknown3);
         data_walker.write_bytes(&self.unknowns);
 
         data_walker.write::<u8>(0xFF);
@@ -231,7 +272,26 @@ pub struct MagicInfo {
     recast_time: u8,
     level_required: BTreeMap<JobEnum, u16>,
     id: u16,
-    icon_id: u8,
+    icon_id: u16,
+    /// 0xFFFF for spells without a high resolution icon.
+    hq_icon_id: u16,
+    requirements: u8,
+    range: u8,
+    /// Radius of the area of effect, 0 for single target spells.
+    area_range: u8,
+    area_type: u8,
+    // None of these match the spell id on the retail menu DAT, so the recast
+    // timer id and spell variant can't be told apart from them yet.
+    unknown1: u32,
+    unknown2: u32,
+    unknown3: u16,
+    unknown4: u16,
+    unknown5: u16,
+    unknown6: u16,
+    unknown7: u16,
+    unknown8: u16,
+    /// Jobs that only learn the spell through job points or merits.
+    job_point_jobs: JobFlag,
 
     #[serde(with = "serde_hex")]
     unknowns: Vec<u8>,
@@ -270,6 +330,20 @@ impl SectionInfo for MagicInfo {
                 .collect(),
             id: data_walker.step()?,
             icon_id: data_walker.step()?,
+            hq_icon_id: data_walker.step()?,
+            requirements: data_walker.step()?,
+            range: data_walker.step()?,
+            area_range: data_walker.step()?,
+            area_type: data_walker.step()?,
+            unknown1: data_walker.step()?,
+            unknown2: data_walker.step()?,
+            unknown3: data_walker.step()?,
+            unknown4: data_walker.step()?,
+            unknown5: data_walker.step()?,
+            unknown6: data_walker.step()?,
+            unknown7: data_walker.step()?,
+            unknown8: data_walker.step()?,
+            job_point_jobs: JobFlag::from_bits(data_walker.step::<u32>()?).unwrap_or_default(),
 
             unknowns: data_walker
                 .take_bytes(data_walker.remaining() - 1)?
@@ -309,6 +383,20 @@ impl SectionInfo for MagicInfo {
 
         data_walker.write(self.id);
         data_walker.write(self.icon_id);
+        data_walker.write(self.hq_icon_id);
+        data_walker.write(self.requirements);
+        data_walker.write(self.range);
+        data_walker.write(self.area_range);
+        data_walker.write(self.area_type);
+        data_walker.write(self.unknown1);
+        data_walker.write(self.unknown2);
+        data_walker.write(self.unknown3);
+        data_walker.write(self.unknown4);
+        data_walker.write(self.unknown5);
+        data_walker.write(self.unknown6);
+        data_walker.write(self.unknown7);
+        data_walker.write(self.unknown8);
+        data_walker.write(self.job_point_jobs.bits());
         data_walker.write_bytes(&self.unknowns);
 
         data_walker.write::<u8>(0xFF);
@@ -321,34 +409,6 @@ impl SectionInfo for MagicInfo {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MonInfo {
-    #[serde(with = "serde_hex")]
-    unknowns: Vec<u8>,
-}
-
-impl SectionInfo for MonInfo {
-    #[inline]
-    fn entry_size() -> usize {
-        64
-    }
-
-    fn parse<T: ByteWalker>(walker: &mut T) -> Result<MonInfo> {
-        let data_bytes = walker.take_bytes(Self::entry_size())?.to_vec();
-        let mut data_walker = BufferedByteWalker::on(data_bytes);
-
-        let info = MonInfo {
-            unknowns: data_walker.take_bytes(data_walker.remaining())?.to_vec(),
-        };
-
-        Ok(info)
-    }
-
-    fn write<T: WritingByteWalker>(&self, _walker: &mut T) -> Result<()> {
-        todo!()
-    }
-}
-
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct MenuTable {
     sections: Vec<Section>,
@@ -411,9 +471,38 @@ impl DatFormat for MenuTable {
 mod tests {
     use std::{fs::File, io::BufWriter, path::PathBuf};
 
-    use crate::dat_format::DatFormat;
+    use crate::{dat_format::DatFormat, flags::JobFlag};
+
+    use super::{AbilityInfo, MagicInfo, MenuTable, Section};
 
-    use super::MenuTable;
+    fn load_menu_table() -> MenuTable {
+        let mut dat_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        dat_path.push("resources/test/menu.DAT");
+
+        MenuTable::from_path_checked(&dat_path).unwrap()
+    }
+
+    fn magic_info(table: &MenuTable, index: u16) -> &MagicInfo {
+        table
+            .sections
+            .iter()
+            .find_map(|section| match section {
+                Section::Mgc_(magic) => magic.iter().find(|info| info.index == index),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn ability_info(table: &MenuTable, id: u16) -> &AbilityInfo {
+        table
+            .sections
+            .iter()
+            .find_map(|section| match section {
+                Section::Comm(abilities) => abilities.iter().find(|info| info.id == id),
+                _ => None,
+            })
+            .unwrap()
+    }
 
     #[test]
     pub fn menu_table() {
@@ -423,7 +512,54 @@ mod tests {
         MenuTable::check_path(&dat_path).unwrap();
         let res = MenuTable::from_path_checked(&dat_path).unwrap();
 
-        let file = File::create("menu.yml").unwrap();
+        let file = File::create(std::env::temp_dir().join("menu.yml")).unwrap();
         serde_yaml::to_writer(BufWriter::new(file), &res).unwrap();
     }
+
+    #[test]
+    pub fn roundtrip() {
+        let mut dat_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        dat_path.push("resources/test/menu.DAT");
+
+        let bytes = std::fs::read(&dat_path).unwrap();
+        let res = MenuTable::from_path_checked(&dat_path).unwrap();
+
+        let yaml = serde_yaml::to_string(&res).unwrap();
+        let res: MenuTable = serde_yaml::from_str(&yaml).unwrap();
+
+        assert_eq!(res.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    pub fn magic_info_fields() {
+        let table = load_menu_table();
+
+        let cure = magic_info(&table, 1);
+        assert_eq!(cure.range, 12);
+        assert_eq!(cure.area_range, 0);
+
+        let curaga = magic_info(&table, 7);
+        assert_eq!(curaga.area_range, 8);
+        assert_eq!(curaga.area_type, 1);
+
+        let drain_iii = magic_info(&table, 880);
+        assert_eq!(drain_iii.job_point_jobs, JobFlag::DRK);
+    }
+
+    #[test]
+    pub fn ability_info_fields() {
+        let table = load_menu_table();
+
+        // Weapon skills are used in melee range
+        let weapon_skill = ability_info(&table, 1);
+        assert_eq!(weapon_skill.range, 2);
+        assert_eq!(weapon_skill.area_range, 0);
+
+        let benediction = ability_info(&table, 530);
+        assert_eq!(benediction.area_range, 10);
+        assert_eq!(benediction.area_type, 1);
+
+        let monster_ability = ability_info(&table, 1793);
+        assert_eq!(monster_ability.required_level, 1);
+    }
 }
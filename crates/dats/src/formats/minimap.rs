@@ -0,0 +1,176 @@
+use anyhow::{anyhow, Result};
+use common::{byte_walker::ByteWalker, writing_byte_walker::WritingByteWalker};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    dat_format::DatFormat,
+    external_files::ExternalFiles,
+    image::{DxtCompression, XiImage, XiImageFormat, XiImageType},
+    serde_hex,
+};
+
+const HEADER_SIZE: usize = 0x10;
+const HEADER_UNKNOWNS_SIZE: usize = 12;
+
+/// Minimap DATs are padded to a multiple of this size.
+const MINIMAP_ALIGNMENT: usize = 0x10;
+
+/// A map of one floor of a zone.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Minimap {
+    /// Header values before the image size, which aren't identified yet.
+    #[serde(with = "serde_hex")]
+    unknowns: Vec<u8>,
+
+    /// Reference to the map PNG, which is exported next to the raw data file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    image: Option<String>,
+
+    #[serde(skip)]
+    image_bytes: Vec<u8>,
+}
+
+impl Minimap {
+    fn parse_header<T: ByteWalker>(walker: &mut T) -> Result<(Vec<u8>, usize)> {
+        let unknowns = walker.take_bytes(HEADER_UNKNOWNS_SIZE)?.to_vec();
+        let image_size = walker.step::<u32>()? as usize;
+
+        if HEADER_SIZE + image_size > walker.len() {
+            return Err(anyhow!(
+                "Map image of {} bytes does not fit in the DAT.",
+                image_size
+            ));
+        }
+
+        // The map image is the only content, so the DAT size follows from it.
+        let expected_len = (HEADER_SIZE + image_size).next_multiple_of(MINIMAP_ALIGNMENT);
+        if walker.len() != expected_len {
+            return Err(anyhow!(
+                "DAT of {} bytes does not match a map image of {} bytes.",
+                walker.len(),
+                image_size
+            ));
+        }
+
+        Ok((unknowns, image_size))
+    }
+
+    pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        let (unknowns, image_size) = Self::parse_header(walker)?;
+        let image_bytes = walker.take_bytes(image_size)?.to_vec();
+
+        let padding = walker.remaining();
+        walker.expect_n_msg::<u8>(0, padding, "Padding after map image")?;
+
+        Ok(Minimap {
+            unknowns,
+            image: None,
+            image_bytes,
+        })
+    }
+
+    pub fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        if self.unknowns.len() != HEADER_UNKNOWNS_SIZE {
+            return Err(anyhow!(
+                "Expected {} header bytes before the map image, got {}.",
+                HEADER_UNKNOWNS_SIZE,
+                self.unknowns.len()
+            ));
+        }
+
+        walker.write_bytes(&self.unknowns);
+        walker.write(self.image_bytes.len() as u32);
+        walker.write_bytes(&self.image_bytes);
+
+        walker.set_size(walker.offset().next_multiple_of(MINIMAP_ALIGNMENT));
+
+        Ok(())
+    }
+}
+
+impl DatFormat for Minimap {
+    fn from<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        Minimap::parse(walker)
+    }
+
+    fn check_type<T: ByteWalker>(walker: &mut T) -> Result<()> {
+        Minimap::parse_header(walker)?;
+        XiImageType::from(walker.step::<u8>()?)?;
+
+        Ok(())
+    }
+
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        self.write(walker)
+    }
+
+    fn export_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        let png_bytes = XiImage::bytes_to_png(&self.image_bytes)
+            .map_err(|err| anyhow!("Failed to export map image: {}", err))?;
+        self.image = Some(files.write("map.png", &png_bytes)?);
+
+        Ok(())
+    }
+
+    fn import_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        let image = self
+            .image
+            .as_ref()
+            .ok_or(anyhow!("Missing reference to the map image."))?;
+
+        // Only used for maps that were not exported from a DAT, since the PNG otherwise
+        // contains the original image to take the header and format from.
+        let template = XiImage::template(
+            XiImageFormat::DirectX {
+                compression: DxtCompression::Dxt3,
+                unknown1: 0,
+            },
+            "map     ",
+            "map     ",
+            32,
+        );
+
+        self.image_bytes = XiImage::png_to_bytes(&files.read(image)?, &template, usize::MAX)
+            .map_err(|err| anyhow!("Failed to import map image: {}", err))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dat_format::DatFormat,
+        image::{XiImage, XiImageFormat},
+    };
+
+    use super::Minimap;
+
+    #[test]
+    pub fn minimap() {
+        let image_bytes = XiImage::template(XiImageFormat::BitmapA, "map     ", "map_0001", 32)
+            .with_pixels(4, 4, [0x20, 0x40, 0x60, 0xFF].repeat(16))
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+
+        let unknowns = (1..=12).collect::<Vec<u8>>();
+        let minimap = Minimap {
+            unknowns: unknowns.clone(),
+            image: None,
+            image_bytes,
+        };
+
+        let bytes = minimap.to_bytes().unwrap();
+        assert_eq!(bytes.len() % 0x10, 0);
+
+        let res = Minimap::from_bytes_checked(&bytes).unwrap();
+        assert_eq!(res.unknowns, unknowns);
+        assert_eq!(res.image_bytes, minimap.image_bytes);
+
+        // The image must fit in the DAT, and be followed by nothing but padding.
+        assert!(Minimap::from_bytes_checked(&bytes[..0x20]).is_err());
+        assert!(Minimap::from_bytes_checked(&[bytes.as_slice(), &[0; 0x10]].concat()).is_err());
+    }
+}
@@ -1,11 +1,15 @@
+pub mod auto_translate;
 pub mod dialog;
 pub mod dmsg;
 pub mod dmsg1_string_table;
 pub mod dmsg2_string_table;
 pub mod dmsg3_string_table;
 pub mod entity_names;
+pub mod font;
 pub mod item_info;
 pub mod menu_table;
+pub mod minimap;
+pub mod sound;
 pub mod status_info;
 pub mod string_table;
 pub mod xistring_table;
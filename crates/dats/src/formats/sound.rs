@@ -0,0 +1,565 @@
+use anyhow::{anyhow, Result};
+use common::{byte_walker::ByteWalker, writing_byte_walker::WritingByteWalker};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    audio::{
+        adpcm::{self, AdpcmState, SAMPLES_PER_BLOCK},
+        Wav,
+    },
+    dat_format::DatFormat,
+    external_files::ExternalFiles,
+    serde_hex,
+};
+
+/// Headers of both containers end with the channel count and the block alignment.
+const BGW_HEADER_SIZE: usize = 0x30;
+const SPW_HEADER_SIZE: usize = 0x2C;
+
+/// The sample rate is stored as the sum of two values, of which the top bit is ignored.
+const SAMPLE_RATE_MASK: u32 = 0x7FFFFFFF;
+
+const PCM_BLOCK_SIZE: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoundContainer {
+    /// Music, from BGW files.
+    Bgw,
+    /// Sound effects, from SPW files.
+    Spw,
+}
+
+impl SoundContainer {
+    fn magic(&self) -> &'static [u8] {
+        match self {
+            SoundContainer::Bgw => b"BGMStream\0\0\0",
+            SoundContainer::Spw => b"SeWave\0\0",
+        }
+    }
+
+    fn header_size(&self) -> usize {
+        match self {
+            SoundContainer::Bgw => BGW_HEADER_SIZE,
+            SoundContainer::Spw => SPW_HEADER_SIZE,
+        }
+    }
+
+    fn from_magic<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        let container = [SoundContainer::Bgw, SoundContainer::Spw]
+            .into_iter()
+            .find(|container| {
+                let magic = container.magic();
+                walker
+                    .read_bytes_at(0, magic.len())
+                    .is_ok_and(|bytes| bytes == magic)
+            })
+            .ok_or(anyhow!("Not a BGW or SPW sound file."))?;
+        walker.skip(container.magic().len());
+
+        if walker.len() < container.header_size() {
+            return Err(anyhow!("Sound file is smaller than its header."));
+        }
+
+        Ok(container)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleFormat {
+    Adpcm,
+    Pcm,
+}
+
+impl SampleFormat {
+    fn from_u32(value: u32) -> Result<Self> {
+        match value {
+            0 => Ok(SampleFormat::Adpcm),
+            1 => Ok(SampleFormat::Pcm),
+            3 => Err(anyhow!("ATRAC3 sound files are not supported.")),
+            _ => Err(anyhow!("Unknown sample format: {}", value)),
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        match self {
+            SampleFormat::Adpcm => 0,
+            SampleFormat::Pcm => 1,
+        }
+    }
+
+    fn block_size(&self) -> usize {
+        match self {
+            SampleFormat::Adpcm => adpcm::BLOCK_SIZE,
+            SampleFormat::Pcm => PCM_BLOCK_SIZE,
+        }
+    }
+
+    fn frames_per_block(&self) -> usize {
+        match self {
+            SampleFormat::Adpcm => SAMPLES_PER_BLOCK,
+            SampleFormat::Pcm => 1,
+        }
+    }
+}
+
+/// A music or sound effect file. The audio itself is exported as a WAV file,
+/// which also holds the sample rate, channels and loop point.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SoundFile {
+    pub container: SoundContainer,
+    pub id: u32,
+    pub format: SampleFormat,
+
+    /// The two values the sample rate is the sum of. They are kept as long as
+    /// the sample rate of the WAV file matches them.
+    sample_rate_parts: [u32; 2],
+    unknown1: u8,
+    unknown2: u8,
+    /// Block alignment of the header, which is the number of samples per block for ADPCM.
+    block_align: u8,
+    /// Bytes between the header and the stream.
+    #[serde(with = "serde_hex")]
+    header_padding: Vec<u8>,
+
+    /// Reference to the WAV file, which is exported next to the raw data file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    wav: Option<String>,
+
+    #[serde(skip)]
+    channels: u8,
+    /// Block where playback continues after reaching the end.
+    #[serde(skip)]
+    loop_start: Option<u32>,
+    /// Encoded blocks, interleaved by channel.
+    #[serde(skip)]
+    stream: Vec<u8>,
+}
+
+fn decode_stream(stream: &[u8], format: SampleFormat, channels: usize) -> Result<Vec<i16>> {
+    let block_size = format.block_size();
+    if channels == 0 || stream.len() % (block_size * channels) != 0 {
+        return Err(anyhow!(
+            "Stream of {} bytes does not fit {} channels.",
+            stream.len(),
+            channels
+        ));
+    }
+
+    match format {
+        SampleFormat::Pcm => Ok(stream
+            .chunks(PCM_BLOCK_SIZE)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect()),
+        SampleFormat::Adpcm => {
+            let mut states = vec![AdpcmState::default(); channels];
+            let mut samples = Vec::with_capacity(stream.len() / block_size * SAMPLES_PER_BLOCK);
+
+            for frame_blocks in stream.chunks(block_size * channels) {
+                let decoded = frame_blocks
+                    .chunks(block_size)
+                    .zip(&mut states)
+                    .map(|(block, state)| adpcm::decode_block(block, state))
+                    .collect::<Result<Vec<_>>>()?;
+
+                for idx in 0..SAMPLES_PER_BLOCK {
+                    samples.extend(decoded.iter().map(|channel| channel[idx]));
+                }
+            }
+
+            Ok(samples)
+        }
+    }
+}
+
+fn encode_stream(samples: &[i16], format: SampleFormat, channels: usize) -> Vec<u8> {
+    match format {
+        SampleFormat::Pcm => samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect(),
+        SampleFormat::Adpcm => {
+            let mut states = vec![AdpcmState::default(); channels];
+            let mut stream = vec![];
+
+            // The last block is filled up with silence.
+            for frames in samples.chunks(SAMPLES_PER_BLOCK * channels) {
+                for (channel, state) in states.iter_mut().enumerate() {
+                    let mut block_samples = [0; SAMPLES_PER_BLOCK];
+                    frames
+                        .iter()
+                        .skip(channel)
+                        .step_by(channels)
+                        .zip(&mut block_samples)
+                        .for_each(|(sample, block_sample)| *block_sample = *sample);
+
+                    stream.extend(adpcm::encode_block(&block_samples, state));
+                }
+            }
+
+            stream
+        }
+    }
+}
+
+impl SoundFile {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate_parts[0].wrapping_add(self.sample_rate_parts[1]) & SAMPLE_RATE_MASK
+    }
+
+    pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        let container = SoundContainer::from_magic(walker)?;
+
+        // The SPW header has the size before the format, and no padding after the magic.
+        let (format, file_size) = match container {
+            SoundContainer::Bgw => {
+                let format = walker.step::<u32>()?;
+                (format, walker.step::<u32>()?)
+            }
+            SoundContainer::Spw => {
+                let file_size = walker.step::<u32>()?;
+                (walker.step::<u32>()?, file_size)
+            }
+        };
+        let format = SampleFormat::from_u32(format)?;
+        if file_size as usize != walker.len() {
+            return Err(anyhow!(
+                "Sound file should be {} bytes, but is {} bytes.",
+                file_size,
+                walker.len()
+            ));
+        }
+
+        let id = walker.step::<u32>()?;
+        let block_count = walker.step::<u32>()? as usize;
+        // Counted from 1, with 0 for sounds that don't loop.
+        let loop_start = walker.step::<u32>()?.checked_sub(1);
+        let sample_rate_parts = [walker.step::<u32>()?, walker.step::<u32>()?];
+        let data_offset = walker.step::<u32>()? as usize;
+        let unknown1 = walker.step::<u8>()?;
+        let unknown2 = walker.step::<u8>()?;
+        let channels = walker.step::<u8>()?;
+        let block_align = walker.step::<u8>()?;
+
+        if format == SampleFormat::Adpcm && block_align as usize != SAMPLES_PER_BLOCK {
+            return Err(anyhow!(
+                "ADPCM with blocks of {} samples is not supported.",
+                block_align
+            ));
+        }
+        if data_offset < walker.offset() || data_offset > walker.len() {
+            return Err(anyhow!(
+                "Invalid offset of the sound data: {:#X}",
+                data_offset
+            ));
+        }
+        let header_padding = walker.take_bytes(data_offset - walker.offset())?.to_vec();
+
+        let stream_size = block_count * channels as usize * format.block_size();
+        if stream_size > walker.remaining() {
+            return Err(anyhow!(
+                "{} blocks of {} channels need {} bytes, but {} bytes are left.",
+                block_count,
+                channels,
+                stream_size,
+                walker.remaining()
+            ));
+        }
+        let stream = walker.take_bytes(stream_size)?.to_vec();
+        walker.expect_n_msg::<u8>(0, walker.remaining(), "Padding after sound data")?;
+
+        Ok(SoundFile {
+            container,
+            id,
+            format,
+            sample_rate_parts,
+            unknown1,
+            unknown2,
+            block_align,
+            header_padding,
+            wav: None,
+            channels,
+            loop_start,
+            stream,
+        })
+    }
+
+    pub fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        if self.channels == 0 {
+            return Err(anyhow!("Sound file without channels."));
+        }
+        let block_count = self.stream.len() / (self.channels as usize * self.format.block_size());
+        let data_offset = self.container.header_size() + self.header_padding.len();
+        let file_size = (data_offset + self.stream.len()) as u32;
+
+        walker.write_bytes(self.container.magic());
+        match self.container {
+            SoundContainer::Bgw => {
+                walker.write(self.format.to_u32());
+                walker.write(file_size);
+            }
+            SoundContainer::Spw => {
+                walker.write(file_size);
+                walker.write(self.format.to_u32());
+            }
+        }
+
+        walker.write(self.id);
+        walker.write(block_count as u32);
+        walker.write(self.loop_start.map(|block| block + 1).unwrap_or(0));
+        walker.write(self.sample_rate_parts[0]);
+        walker.write(self.sample_rate_parts[1]);
+        walker.write(data_offset as u32);
+        walker.write(self.unknown1);
+        walker.write(self.unknown2);
+        walker.write(self.channels);
+        walker.write(self.block_align);
+        walker.write_bytes(&self.header_padding);
+
+        walker.write_bytes(&self.stream);
+
+        Ok(())
+    }
+
+    pub fn to_wav(&self) -> Result<Wav> {
+        Ok(Wav {
+            sample_rate: self.sample_rate(),
+            channels: self.channels as u16,
+            samples: decode_stream(&self.stream, self.format, self.channels as usize)?,
+            loop_start: self
+                .loop_start
+                .map(|block| block * self.format.frames_per_block() as u32),
+            original_bytes: Some(self.stream.clone()),
+        })
+    }
+
+    /// Takes the audio from a WAV file. If it was exported and not modified since,
+    /// the original stream is kept instead of encoding it again.
+    pub fn set_wav(&mut self, wav: &Wav) -> Result<()> {
+        self.channels = u8::try_from(wav.channels)
+            .ok()
+            .filter(|channels| *channels > 0)
+            .ok_or(anyhow!("Unsupported channel count: {}", wav.channels))?;
+        if wav.sample_rate != self.sample_rate() {
+            self.sample_rate_parts = [wav.sample_rate, 0];
+        }
+        if self.format == SampleFormat::Adpcm {
+            self.block_align = SAMPLES_PER_BLOCK as u8;
+        }
+
+        let channels = self.channels as usize;
+        let original_stream = wav.original_bytes.as_ref().filter(|stream| {
+            decode_stream(stream, self.format, channels).is_ok_and(|samples| samples == wav.samples)
+        });
+
+        self.stream = match original_stream {
+            Some(stream) => stream.clone(),
+            None => encode_stream(&wav.samples, self.format, channels),
+        };
+
+        // Loops can only start at the beginning of a block.
+        self.loop_start = wav
+            .loop_start
+            .map(|frame| frame / self.format.frames_per_block() as u32);
+
+        Ok(())
+    }
+}
+
+impl DatFormat for SoundFile {
+    fn from<T: ByteWalker>(walker: &mut T) -> Result<Self> {
+        SoundFile::parse(walker)
+    }
+
+    fn check_type<T: ByteWalker>(walker: &mut T) -> Result<()> {
+        SoundContainer::from_magic(walker)?;
+        Ok(())
+    }
+
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        self.write(walker)
+    }
+
+    fn export_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        let wav = self
+            .to_wav()
+            .map_err(|err| anyhow!("Failed to decode sound {}: {}", self.id, err))?;
+        self.wav = Some(files.write("audio.wav", &wav.to_bytes())?);
+
+        Ok(())
+    }
+
+    fn import_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        let wav = self
+            .wav
+            .as_ref()
+            .ok_or(anyhow!("Missing reference to the WAV file."))?;
+
+        let wav = Wav::from_bytes(&files.read(wav)?)?;
+        self.set_wav(&wav)
+            .map_err(|err| anyhow!("Failed to encode sound {}: {}", self.id, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{audio::Wav, dat_format::DatFormat};
+
+    use super::{SampleFormat, SoundContainer, SoundFile};
+
+    fn sound_file(format: SampleFormat, channels: u8) -> SoundFile {
+        SoundFile {
+            container: SoundContainer::Bgw,
+            id: 115,
+            format,
+            sample_rate_parts: [44100, 0],
+            unknown1: 0,
+            unknown2: 0,
+            block_align: 16,
+            header_padding: vec![],
+            wav: None,
+            channels,
+            loop_start: None,
+            stream: vec![],
+        }
+    }
+
+    fn tone(frames: usize, channels: usize) -> Vec<i16> {
+        (0..frames * channels)
+            .map(|idx| (((idx / channels) as f64 / 8.0).sin() * 8000.0) as i16)
+            .collect()
+    }
+
+    fn le(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    /// ADPCM blocks of a channel at 1 and a channel at -1, without prediction.
+    fn stereo_blocks() -> Vec<u8> {
+        [
+            [0x0C, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11],
+            [0x0C, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+        ]
+        .concat()
+    }
+
+    #[test]
+    pub fn bgw_header() {
+        // Laid out like vgmstream reads BGW files, with the sample rate split in two values.
+        let stream = stereo_blocks();
+        let bytes = [
+            b"BGMStream\0\0\0".to_vec(),
+            le(&[0, 0x30 + 4 + stream.len() as u32, 115, 1, 1]),
+            le(&[0x8000_0000, 44100, 0x34]),
+            vec![0x7F, 0x10, 2, 16],
+            vec![0xAB; 4],
+            stream.clone(),
+        ]
+        .concat();
+
+        let res = SoundFile::from_bytes_checked(&bytes).unwrap();
+        assert_eq!(res.container, SoundContainer::Bgw);
+        assert_eq!(res.format, SampleFormat::Adpcm);
+        assert_eq!(res.id, 115);
+        assert_eq!(res.header_padding, vec![0xAB; 4]);
+        assert_eq!(res.stream, stream);
+
+        let wav = res.to_wav().unwrap();
+        assert_eq!(wav.sample_rate, 44100);
+        assert_eq!(wav.channels, 2);
+        assert_eq!(wav.loop_start, Some(0));
+        assert_eq!(wav.samples, [1, -1].repeat(16));
+
+        assert_eq!(res.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    pub fn spw_header() {
+        // Unlike BGW, the magic is 8 bytes and the file size comes before the format.
+        let stream = [0x34, 0x12, 0xCC, 0xED];
+        let bytes = [
+            b"SeWave\0\0".to_vec(),
+            le(&[0x2C + 4, 1, 2004, 2, 0]),
+            le(&[11025, 0, 0x2C]),
+            vec![0, 0, 1, 0],
+            stream.to_vec(),
+        ]
+        .concat();
+
+        let res = SoundFile::from_bytes_checked(&bytes).unwrap();
+        assert_eq!(res.container, SoundContainer::Spw);
+        assert_eq!(res.format, SampleFormat::Pcm);
+        assert_eq!(res.id, 2004);
+
+        let wav = res.to_wav().unwrap();
+        assert_eq!(wav.sample_rate, 11025);
+        assert_eq!(wav.loop_start, None);
+        assert_eq!(wav.samples, vec![0x1234, -0x1234]);
+
+        assert_eq!(res.to_bytes().unwrap(), bytes);
+
+        // The file size has to match.
+        assert!(SoundFile::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+    }
+
+    #[test]
+    pub fn adpcm_wav_roundtrip() {
+        let mut sound = sound_file(SampleFormat::Adpcm, 2);
+        sound
+            .set_wav(&Wav {
+                sample_rate: 44100,
+                channels: 2,
+                samples: tone(40, 2),
+                loop_start: Some(35),
+                original_bytes: None,
+            })
+            .unwrap();
+
+        // Encoded as 3 blocks per channel, looping from the block of the loop point.
+        assert_eq!(sound.stream.len(), 3 * 2 * 9);
+        assert_eq!(sound.loop_start, Some(2));
+
+        let bytes = sound.to_bytes().unwrap();
+        let res = SoundFile::from_bytes_checked(&bytes).unwrap();
+        assert_eq!(res.container, SoundContainer::Bgw);
+        assert_eq!(res.channels, 2);
+
+        let wav = Wav::from_bytes(&res.to_wav().unwrap().to_bytes()).unwrap();
+        assert_eq!(wav.samples.len(), 48 * 2);
+        assert_eq!(wav.loop_start, Some(32));
+        for (decoded, original) in wav.samples.iter().zip(tone(40, 2)) {
+            assert!((*decoded as i32 - original as i32).abs() < 512);
+        }
+
+        // An unmodified WAV gives the original stream.
+        let mut reimported = sound_file(SampleFormat::Adpcm, 2);
+        reimported.set_wav(&wav).unwrap();
+        assert_eq!(reimported.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    pub fn pcm() {
+        let mut sound = sound_file(SampleFormat::Pcm, 1);
+        sound.container = SoundContainer::Spw;
+        sound
+            .set_wav(&Wav {
+                sample_rate: 22050,
+                channels: 1,
+                samples: tone(10, 1),
+                loop_start: None,
+                original_bytes: None,
+            })
+            .unwrap();
+
+        let res = SoundFile::from_bytes_checked(&sound.to_bytes().unwrap()).unwrap();
+        assert_eq!(res.container, SoundContainer::Spw);
+        assert_eq!(res.to_wav().unwrap().samples, tone(10, 1));
+        assert_eq!(res.to_wav().unwrap().sample_rate, 22050);
+        assert_eq!(res.loop_start, None);
+
+        assert!(SoundFile::from_bytes(&b"RIFF".repeat(16)).is_err());
+    }
+}
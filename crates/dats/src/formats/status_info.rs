@@ -7,9 +7,10 @@ use common::{
 use encoding::{decoder::Decoder, encoder::Encoder};
 use serde_derive::{Deserialize, Serialize};
 
-use crate::serde_base64;
 use crate::{
     dat_format::DatFormat,
+    external_files::ExternalFiles,
+    image::{XiImage, XiImageFormat},
     utils::{decode_data_block, encode_data_block},
 };
 
@@ -20,7 +21,13 @@ pub struct StatusInfo {
 
     flag: u16,
 
-    #[serde(with = "serde_base64")]
+    /// Reference to the icon, which is exported next to the raw data file as a PNG,
+    /// or as raw bytes if it can't be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    icon: Option<String>,
+
+    #[serde(skip)]
     icon_bytes: Vec<u8>,
 }
 
@@ -55,6 +62,7 @@ impl StatusInfo {
             id,
             flag,
             description,
+            icon: None,
             icon_bytes,
         })
     }
@@ -80,6 +88,14 @@ impl StatusInfo {
 
         walker.write_bytes(&data_bytes);
 
+        if self.icon_bytes.len() > MAX_ICON_SIZE {
+            return Err(anyhow!(
+                "Icon for status {} is {} bytes, but at most {} bytes can fit.",
+                self.id,
+                self.icon_bytes.len(),
+                MAX_ICON_SIZE
+            ));
+        }
         walker.write(self.icon_bytes.len() as u32);
         walker.write_bytes(&self.icon_bytes);
 
@@ -99,6 +115,9 @@ pub struct StatusInfoTable {
 
 const ENTRY_SIZE: usize = 0x1800;
 
+/// Icons are placed after the data at 0x280, with a leading size and an ending 0xFF.
+const MAX_ICON_SIZE: usize = ENTRY_SIZE - 0x280 - 4 - 1;
+
 impl StatusInfoTable {
     pub fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
         if walker.len() % ENTRY_SIZE != 0 {
@@ -144,15 +163,64 @@ impl DatFormat for StatusInfoTable {
     fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
         self.write(walker)
     }
+
+    fn export_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        // Some ids are used by more than one status, so icons are named by their position.
+        for (index, status_info) in self.status_infos.iter_mut().enumerate() {
+            if status_info.icon_bytes.is_empty() {
+                continue;
+            }
+
+            status_info.icon =
+                Some(files.write_image(&format!("icons/{index}"), &status_info.icon_bytes)?);
+        }
+
+        Ok(())
+    }
+
+    fn import_external_files(&mut self, files: &ExternalFiles) -> Result<()> {
+        for status_info in &mut self.status_infos {
+            let Some(icon) = &status_info.icon else {
+                continue;
+            };
+
+            // Status icons are named like "st00_32 " in the "sts_icon" category.
+            let mut image_id = format!("st{:02}_32", status_info.id);
+            image_id.truncate(8);
+            let template = XiImage::template(
+                XiImageFormat::BitmapA,
+                "sts_icon",
+                &format!("{:<8}", image_id),
+                32,
+            );
+
+            status_info.icon_bytes =
+                files
+                    .read_image(icon, &template, MAX_ICON_SIZE)
+                    .map_err(|err| {
+                        anyhow!(
+                            "Failed to import icon for status {}: {}",
+                            status_info.id,
+                            err
+                        )
+                    })?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
-    use crate::dat_format::DatFormat;
+    use crate::{
+        dat_format::DatFormat,
+        external_files::ExternalFiles,
+        image::{XiImage, XiImageFormat},
+    };
 
-    use super::StatusInfoTable;
+    use super::{StatusInfoTable, MAX_ICON_SIZE};
 
     #[test]
     pub fn status_infos() {
@@ -172,4 +240,53 @@ mod tests {
             "Ullegore is making you forget the true meaning of \"fun\"!".to_string()
         );
     }
+
+    #[test]
+    pub fn status_icons() {
+        let mut dat_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        dat_path.push("resources/test/status_infos.DAT");
+
+        let res = StatusInfoTable::from_path(&dat_path).unwrap();
+        let template = XiImage::template(XiImageFormat::BitmapA, "sts_icon", "st00_32 ", 32);
+
+        for status_info in res.status_infos.iter().take(20) {
+            let png_bytes = XiImage::bytes_to_png(&status_info.icon_bytes).unwrap();
+            let icon_bytes = XiImage::png_to_bytes(&png_bytes, &template, MAX_ICON_SIZE).unwrap();
+            assert_eq!(icon_bytes, status_info.icon_bytes);
+        }
+
+        // Re-encoding the decoded icon gives the same bytes.
+        let icon = XiImage::from_bytes(&res.status_infos[0].icon_bytes).unwrap();
+        assert_eq!(icon.to_bytes().unwrap(), res.status_infos[0].icon_bytes);
+    }
+
+    #[test]
+    pub fn status_icons_external_files() {
+        let mut dat_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        dat_path.push("resources/test/status_infos.DAT");
+
+        let mut res = StatusInfoTable::from_path(&dat_path).unwrap();
+        let original_icons: Vec<_> = res
+            .status_infos
+            .iter()
+            .map(|s| s.icon_bytes.clone())
+            .collect();
+
+        let dir = std::env::temp_dir().join("status_icons_external_files");
+        let files = ExternalFiles::for_raw_data_path(&dir.join("status_info.yml")).unwrap();
+        res.export_external_files(&files).unwrap();
+
+        for status_info in &mut res.status_infos {
+            status_info.icon_bytes.clear();
+        }
+        res.import_external_files(&files).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        let icons: Vec<_> = res
+            .status_infos
+            .iter()
+            .map(|s| s.icon_bytes.clone())
+            .collect();
+        assert_eq!(icons, original_icons);
+    }
 }
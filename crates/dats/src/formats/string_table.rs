@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use anyhow::{anyhow, Result};
 use common::{byte_walker::ByteWalker, writing_byte_walker::WritingByteWalker};
-use encoding::decoder::Decoder;
+use encoding::{decoder::Decoder, encoder::Encoder};
 use serde_derive::{Deserialize, Serialize};
 
 use crate::dat_format::DatFormat;
@@ -18,18 +18,21 @@ pub struct StringTable {
     entries: BTreeMap<u32, StringTableEntry>,
 }
 
+const ENTRY_SIZE: usize = 0x40;
+const STRING_SIZE: usize = 59;
+
 impl StringTable {
     fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
-        if walker.len() % 0x40 != 0 {
+        if walker.len() % ENTRY_SIZE != 0 {
             return Err(anyhow!("Not a string table."));
         }
 
-        let entry_count = walker.len() / 0x40;
+        let entry_count = walker.len() / ENTRY_SIZE;
 
         let mut entries = BTreeMap::new();
         for idx in 0..entry_count {
             let id: u32 = walker.step()?;
-            let string = Decoder::decode_simple(walker.take_bytes(59)?)?;
+            let string = Decoder::decode_simple(walker.take_bytes(STRING_SIZE)?)?;
             entries.insert(idx as u32, StringTableEntry { id, string });
 
             walker.expect_msg::<u8>(0xFF, "String entry expected to be ended by 0xFF.")?;
@@ -37,11 +40,36 @@ impl StringTable {
 
         Ok(StringTable { entries })
     }
+
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        for (idx, entry) in &self.entries {
+            walker.write(entry.id);
+
+            let mut encoded_string = Encoder::encode_simple(&entry.string)?;
+            if encoded_string.len() > STRING_SIZE {
+                return Err(anyhow!(
+                    "String for entry {} is {} bytes, but can at most be {} bytes: {}",
+                    idx,
+                    encoded_string.len(),
+                    STRING_SIZE,
+                    entry.string
+                ));
+            }
+
+            // Zero-pad the rest of the string
+            encoded_string.resize(STRING_SIZE, 0x00);
+            walker.write_bytes(&encoded_string);
+
+            walker.write(0xFFu8);
+        }
+
+        Ok(())
+    }
 }
 
 impl DatFormat for StringTable {
-    fn write<T: WritingByteWalker>(&self, _walker: &mut T) -> Result<()> {
-        Err(anyhow!("Not yet implemented"))
+    fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        self.write(walker)
     }
 
     fn from<T: ByteWalker>(walker: &mut T) -> Result<Self> {
@@ -49,14 +77,66 @@ impl DatFormat for StringTable {
     }
 
     fn check_type<T: ByteWalker>(walker: &mut T) -> Result<()> {
-        if walker.len() % 0x40 != 0 {
+        if walker.len() % ENTRY_SIZE != 0 {
             return Err(anyhow!("Does not have a size that matches a string table."));
         }
 
-        let string_end_byte = walker.read_at::<u8>(0x40)?;
+        let string_end_byte = walker.read_at::<u8>(ENTRY_SIZE - 1)?;
         if string_end_byte != 0xFF {
             return Err(anyhow!("Expected strings to be ended by 0xFF."));
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::dat_format::DatFormat;
+
+    use super::{StringTable, StringTableEntry};
+
+    #[test]
+    pub fn roundtrip() {
+        let table = StringTable {
+            entries: BTreeMap::from([
+                (
+                    0,
+                    StringTableEntry {
+                        id: 1,
+                        string: "Fire".to_string(),
+                    },
+                ),
+                (
+                    1,
+                    StringTableEntry {
+                        id: 2,
+                        string: "Blizzard".to_string(),
+                    },
+                ),
+            ]),
+        };
+
+        let bytes = table.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 0x80);
+
+        let res = StringTable::from_bytes_checked(&bytes).unwrap();
+        assert_eq!(res.entries.get(&1).unwrap().string, "Blizzard");
+    }
+
+    #[test]
+    pub fn string_too_long() {
+        let table = StringTable {
+            entries: BTreeMap::from([(
+                0,
+                StringTableEntry {
+                    id: 1,
+                    string: "a".repeat(60),
+                },
+            )]),
+        };
+
+        assert!(table.to_bytes().is_err());
+    }
+}
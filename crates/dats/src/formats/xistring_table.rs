@@ -9,13 +9,50 @@ use crate::dat_format::DatFormat;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct XiStringTable {
-    strings: BTreeMap<u32, String>,
+    unknown2: u32,
+    strings: BTreeMap<u32, XiString>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum XiString {
+    String(String),
+    WithMeta {
+        string: String,
+        unknown1: u16,
+        unknown2: u16,
+        unknown3: u16,
+    },
+}
+
+impl XiString {
+    pub fn string(&self) -> &String {
+        match self {
+            XiString::String(string) => string,
+            XiString::WithMeta { string, .. } => string,
+        }
+    }
+
+    fn meta_values(&self) -> (u16, u16, u16) {
+        match self {
+            XiString::String(_) => (0, 0, 0),
+            XiString::WithMeta {
+                unknown1,
+                unknown2,
+                unknown3,
+                ..
+            } => (*unknown1, *unknown2, *unknown3),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct XiStringMeta {
     offset: u32,
     size: u16,
+    unknown1: u16,
+    unknown2: u16,
+    unknown3: u16,
 }
 
 const HEADER_SIZE: u32 = 0x38;
@@ -43,25 +80,19 @@ impl XiStringTable {
             return Err(anyhow!("unknown1 is {}", unknown1));
         }
 
+        // Differs between DATs, e.g. 304091210 in PolMessages and 304231515 in IngameMessages2.
         let unknown2: u32 = walker.step()?;
-        // TODO: this value is different in at least IngameMessages2, where it is 304231515
-        if unknown2 != 304091210 {
-            return Err(anyhow!("unknown2 is {}", unknown2));
-        }
 
         // Read metadata
         let mut metas = vec![];
         for _ in 0..entry_count {
-            let offset: u32 = walker.step()?;
-            let size: u16 = walker.step()?;
-
-            // TODO: TimeAndPronouns has a 1 here instead of 0
-            walker.expect_msg(0u16, "Unknown meta 1")?;
-
-            walker.expect_msg(0u16, "Unknown meta 2")?;
-            walker.expect_msg(0u16, "Unknown meta 3")?;
-
-            metas.push(XiStringMeta { offset, size });
+            metas.push(XiStringMeta {
+                offset: walker.step()?,
+                size: walker.step()?,
+                unknown1: walker.step()?,
+                unknown2: walker.step()?,
+                unknown3: walker.step()?,
+            });
         }
 
         // Read the strings
@@ -75,10 +106,21 @@ impl XiStringTable {
 
             let string_bytes = walker.take_bytes(meta.size as usize)?;
             let string = Decoder::decode_simple(string_bytes)?;
-            strings.insert(idx as u32, string);
+
+            let entry = if meta.unknown1 == 0 && meta.unknown2 == 0 && meta.unknown3 == 0 {
+                XiString::String(string)
+            } else {
+                XiString::WithMeta {
+                    string,
+                    unknown1: meta.unknown1,
+                    unknown2: meta.unknown2,
+                    unknown3: meta.unknown3,
+                }
+            };
+            strings.insert(idx as u32, entry);
         }
 
-        Ok(XiStringTable { strings })
+        Ok(XiStringTable { unknown2, strings })
     }
 
     pub fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
@@ -92,8 +134,8 @@ impl XiStringTable {
         let encoded_strings = self
             .strings
             .iter()
-            .map(|(idx, string)| {
-                let encoded = Encoder::encode_simple(&string)?;
+            .map(|(idx, entry)| {
+                let encoded = Encoder::encode_simple(entry.string())?;
 
                 Ok((idx, encoded))
             })
@@ -116,7 +158,7 @@ impl XiStringTable {
         walker.write(data_bytes);
 
         walker.write::<u32>(0); // unknown1
-        walker.write::<u32>(304091210); // unknown2
+        walker.write::<u32>(self.unknown2);
 
         // Write metadata for strings
         let mut current_string_offset = 0;
@@ -127,13 +169,17 @@ impl XiStringTable {
                 .unwrap_or_default()
                 + 1; // 1 extra byte for string end
 
+            let (unknown1, unknown2, unknown3) = self
+                .strings
+                .get(&idx)
+                .map(|entry| entry.meta_values())
+                .unwrap_or_default();
+
             walker.write(current_string_offset);
             walker.write(string_len as u16);
-
-            // Unknowns
-            walker.write(0u16);
-            walker.write(0u16);
-            walker.write(0u16);
+            walker.write(unknown1);
+            walker.write(unknown2);
+            walker.write(unknown3);
 
             current_string_offset += string_len as u32;
         }
@@ -170,7 +216,12 @@ impl DatFormat for XiStringTable {
 mod tests {
     use std::path::PathBuf;
 
-    use crate::{dat_format::DatFormat, formats::xistring_table::XiStringTable};
+    use std::collections::BTreeMap;
+
+    use crate::{
+        dat_format::DatFormat,
+        formats::xistring_table::{XiString, XiStringTable},
+    };
 
     #[test]
     pub fn pol_messages() {
@@ -180,14 +231,41 @@ mod tests {
         XiStringTable::check_path(&dat_path).unwrap();
         let res = XiStringTable::from_path_checked(&dat_path).unwrap();
 
+        assert_eq!(res.unknown2, 304091210);
+
         assert_eq!(
-            res.strings.get(&0).unwrap(),
+            res.strings.get(&0).unwrap().string(),
             &"Searching for lobby server.".to_string()
         );
 
         assert_eq!(
-            res.strings.get(&104).unwrap(),
+            res.strings.get(&104).unwrap().string(),
             &"Select a character to play.".to_string()
         );
     }
+
+    #[test]
+    pub fn meta_roundtrip() {
+        let table = XiStringTable {
+            unknown2: 304231515,
+            strings: BTreeMap::from([
+                (0, XiString::String("Morning".to_string())),
+                (
+                    1,
+                    XiString::WithMeta {
+                        string: "he".to_string(),
+                        unknown1: 1,
+                        unknown2: 0,
+                        unknown3: 0,
+                    },
+                ),
+            ]),
+        };
+
+        let bytes = table.to_bytes().unwrap();
+        let res = XiStringTable::from_bytes_checked(&bytes).unwrap();
+
+        assert_eq!(res.unknown2, 304231515);
+        assert_eq!(res.strings, table.strings);
+    }
 }
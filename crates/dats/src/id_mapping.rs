@@ -1,35 +1,43 @@
 use std::sync::OnceLock;
 
 use crate::{
-    base::{Dat, DatByZone},
+    base::{Dat, DatByZone, DatByZoneFloor, PathDat},
     formats::{
-        dialog::Dialog, dmsg2_string_table::Dmsg2StringTable, dmsg3_string_table::Dmsg3StringTable,
-        entity_names::EntityNames, item_info::ItemInfoTable, menu_table::MenuTable,
-        status_info::StatusInfoTable, xistring_table::XiStringTable,
+        auto_translate::AutoTranslateTable, dialog::Dialog, dmsg1_string_table::DmsgStringTable1,
+        dmsg2_string_table::Dmsg2StringTable, dmsg3_string_table::Dmsg3StringTable,
+        entity_names::EntityNames, font::FontTable, item_info::ItemInfoTable,
+        menu_table::MenuTable, minimap::Minimap, sound::SoundFile, status_info::StatusInfoTable,
+        string_table::StringTable, xistring_table::XiStringTable,
     },
+    language::Language,
 };
 
+/// The DATs that hold each kind of content for a language.
+/// Content that is only known to be in a DAT for some languages is left unmapped for the others,
+/// such that it is never read from or written to the DAT of another language.
 #[derive(Debug)]
 pub struct DatIdMapping {
     pub entities: DatByZone<EntityNames>,
     pub dialog: DatByZone<Dialog>,
     pub dialog2: DatByZone<Dialog>,
+    pub minimaps: DatByZoneFloor<Minimap>,
 
     // Global dialog
-    pub monster_skill_names: Dat<Dialog>,
-    pub status_names_dialog: Dat<Dialog>,
-    pub emote_messages: Dat<Dialog>,
-    pub system_messages_1: Dat<Dialog>,
-    pub system_messages_2: Dat<Dialog>,
-    pub system_messages_3: Dat<Dialog>,
-    pub system_messages_4: Dat<Dialog>,
-    pub unity_dialogs: Dat<Dialog>,
+    pub monster_skill_names: Option<Dat<Dialog>>,
+    pub status_names_dialog: Option<Dat<Dialog>>,
+    pub emote_messages: Option<Dat<Dialog>>,
+    pub system_messages_1: Option<Dat<Dialog>>,
+    pub system_messages_2: Option<Dat<Dialog>>,
+    pub system_messages_3: Option<Dat<Dialog>>,
+    pub system_messages_4: Option<Dat<Dialog>>,
+    pub unity_dialogs: Option<Dat<Dialog>>,
 
     // String tables
     pub ability_names: Dat<Dmsg3StringTable>,
     pub ability_descriptions: Dat<Dmsg3StringTable>,
     pub area_names: Dat<Dmsg2StringTable>,
     pub area_names_alt: Dat<Dmsg2StringTable>,
+    pub auto_translate: Dat<AutoTranslateTable>,
     pub character_select: Dat<Dmsg2StringTable>,
     pub chat_filter_types: Dat<Dmsg2StringTable>,
     pub day_names: Dat<Dmsg2StringTable>,
@@ -42,25 +50,34 @@ pub struct DatIdMapping {
     pub key_items: Dat<Dmsg3StringTable>,
     pub menu_items_description: Dat<Dmsg2StringTable>,
     pub menu_items_text: Dat<Dmsg2StringTable>,
+    pub mission_log: Dat<Dmsg3StringTable>,
 
     pub moon_phases: Dat<Dmsg2StringTable>,
     pub pol_messages: Dat<XiStringTable>,
+    pub quest_log: Dat<Dmsg3StringTable>,
     pub race_names: Dat<Dmsg2StringTable>,
     pub region_names: Dat<Dmsg2StringTable>,
     pub spell_names: Dat<Dmsg3StringTable>,
     pub spell_descriptions: Dat<Dmsg3StringTable>,
-    pub status_info: Dat<StatusInfoTable>,
+    pub status_info: Option<Dat<StatusInfoTable>>,
     pub status_names: Dat<Dmsg2StringTable>,
-    pub time_and_pronouns: Dat<XiStringTable>,
+    pub time_and_pronouns: Option<Dat<XiStringTable>>,
     pub titles: Dat<Dmsg3StringTable>,
     pub misc1: Dat<Dmsg2StringTable>,
     pub misc2: Dat<Dmsg2StringTable>,
+    // The older d_msg tables at 55655 and 55656 (55535 and 55536 in Japanese) haven't been
+    // compared with a client to see what they hold, so they are numbered like the other misc DATs.
+    pub misc3: Dat<DmsgStringTable1>,
+    pub misc4: Dat<DmsgStringTable1>,
+    // Same for the 0x40-record string tables at 58 and 59, which are only known for English.
+    pub misc5: Option<Dat<StringTable>>,
+    pub misc6: Option<Dat<StringTable>>,
     pub weather_types: Dat<Dmsg2StringTable>,
 
     // Item data
     pub armor: Dat<ItemInfoTable>,
     pub armor2: Dat<ItemInfoTable>,
-    pub currency: Dat<ItemInfoTable>,
+    pub currency: Option<Dat<ItemInfoTable>>,
     pub general_items: Dat<ItemInfoTable>,
     pub general_items2: Dat<ItemInfoTable>,
     pub puppet_items: Dat<ItemInfoTable>,
@@ -72,15 +89,43 @@ pub struct DatIdMapping {
 
     // Misc data
     pub data_menu: Dat<MenuTable>,
+    pub font: Option<Dat<FontTable>>,
 }
 
-static DAT_ID_MAPPING: OnceLock<DatIdMapping> = OnceLock::new();
+static DAT_ID_MAPPINGS: [OnceLock<DatIdMapping>; 2] = [OnceLock::new(), OnceLock::new()];
 
 impl DatIdMapping {
-    pub fn get() -> &'static Self {
-        DAT_ID_MAPPING.get_or_init(|| {
-            // Entities
-            let mut entities = DatByZone::default();
+    /// Music is kept outside of the ROM directories, in BGW files named after its ID.
+    pub fn music(id: u16) -> PathDat<SoundFile> {
+        PathDat::new(format!("sound/win/music/data/music{:03}.bgw", id))
+    }
+
+    /// Sound effects are kept outside of the ROM directories, in SPW files
+    /// grouped in directories of a thousand.
+    pub fn sound_effect(id: u32) -> PathDat<SoundFile> {
+        PathDat::new(format!("sound/win/se/se{:03}/se{:06}.spw", id / 1000, id))
+    }
+
+    /// The DATs of a language.
+    pub fn get(language: Language) -> &'static Self {
+        DAT_ID_MAPPINGS[language as usize].get_or_init(|| Self::new(language))
+    }
+
+    fn new(language: Language) -> Self {
+        let is_english = language == Language::English;
+
+        // Picks the ID of content that has its own DAT in Japanese clients.
+        let localized = |japanese_id: u32, english_id: u32| match language {
+            Language::Japanese => japanese_id,
+            _ => english_id,
+        };
+        // Content that is only mapped for English clients so far.
+        let english_only = |english_id: u32| is_english.then_some(english_id);
+
+        // Entities
+        let mut entities = DatByZone::default();
+        // Only mapped for English clients so far
+        if is_english {
             // Zones 1-255
             (0..256).into_iter().for_each(|idx| {
                 entities.insert(idx, 6720 + idx);
@@ -93,85 +138,220 @@ impl DatIdMapping {
             (0..256).into_iter().for_each(|idx| {
                 entities.insert(1000 + idx, 67911 + idx);
             });
+        }
 
-            // Dialog text
-            let mut dialog = DatByZone::default();
-            // Zones 0-255
-            (0..256).into_iter().for_each(|idx| {
-                dialog.insert(idx, 6420 + idx);
-            });
-            // Zones 256-512
+        // Dialog text
+        let mut dialog = DatByZone::default();
+        // Zones 0-255
+        let first_dialog = localized(6120, 6420) as usize;
+        (0..256).into_iter().for_each(|idx| {
+            dialog.insert(idx, first_dialog + idx);
+        });
+        // Zones 256-512
+        let first_dialog = localized(85290, 85590) as usize;
+        (0..256).into_iter().for_each(|idx| {
+            dialog.insert(idx + 256, first_dialog + idx);
+        });
+
+        // Secondary dialog text
+        let mut dialog2 = DatByZone::default();
+        // Just whitegate? Only mapped for English clients so far
+        if is_english {
+            dialog2.insert(50, 57945);
+        }
+
+        // Minimaps
+        let mut minimaps = DatByZoneFloor::default();
+        // Main floor of zones 0-255. The maps are labeled with place names, and are only mapped
+        // for English clients so far. These ids haven't been checked against a retail client,
+        // which is why only DATs that parse as minimaps are listed, and the DATs of other floors
+        // aren't known yet.
+        if is_english {
             (0..256).into_iter().for_each(|idx| {
-                dialog.insert(idx + 256, 85590 + idx);
+                minimaps.insert(idx, 0, 5312 + idx);
             });
+        }
 
-            // Secondary dialog text
-            let mut dialog2 = DatByZone::default();
-            // Just whitegate?
-            dialog2.insert(50, 57945);
+        Self {
+            entities,
+            dialog,
+            dialog2,
+            minimaps,
+
+            // Global dialog
+            monster_skill_names: english_only(07035).map(Into::into),
+            status_names_dialog: english_only(07029).map(Into::into),
+            emote_messages: english_only(07025).map(Into::into),
+            system_messages_1: english_only(07023).map(Into::into),
+            system_messages_2: english_only(07031).map(Into::into),
+            system_messages_3: english_only(07021).map(Into::into),
+            system_messages_4: english_only(07027).map(Into::into),
+            unity_dialogs: english_only(07039).map(Into::into),
+
+            // String tables
+            ability_names: localized(55581, 55701).into(),
+            ability_descriptions: localized(55613, 55733).into(),
+            area_names: localized(55345, 55465).into(),
+            area_names_alt: localized(55541, 55661).into(),
+            auto_translate: localized(55545, 55665).into(),
+            character_select: localized(55350, 55470).into(),
+            chat_filter_types: localized(55530, 55650).into(),
+            day_names: localized(55538, 55658).into(),
+            directions: localized(55539, 55659).into(),
+            equipment_locations: localized(55351, 55471).into(),
+            error_messages: localized(55526, 55646).into(),
+            ingame_messages_1: localized(55528, 55648).into(),
+            ingame_messages_2: localized(55529, 55649).into(),
+            job_names: localized(55347, 55467).into(),
+            key_items: localized(55575, 55695).into(),
+            menu_items_description: localized(55531, 55651).into(),
+            menu_items_text: localized(55532, 55652).into(),
+            mission_log: localized(55578, 55698).into(),
+            moon_phases: localized(55540, 55660).into(),
+            pol_messages: localized(55527, 55647).into(),
+            quest_log: localized(55577, 55697).into(),
+            race_names: localized(55349, 55469).into(),
+            region_names: localized(55534, 55654).into(),
+            spell_names: localized(55582, 55702).into(),
+            spell_descriptions: localized(55614, 55734).into(),
+            status_info: english_only(00087).map(Into::into),
+            status_names: localized(55605, 55725).into(),
+            time_and_pronouns: english_only(00063).map(Into::into),
+            titles: localized(55584, 55704).into(),
+            misc1: localized(55525, 55645).into(),
+            misc2: localized(55533, 55653).into(),
+            misc3: localized(55535, 55655).into(),
+            misc4: localized(55536, 55656).into(),
+            misc5: english_only(00058).map(Into::into),
+            misc6: english_only(00059).map(Into::into),
+            weather_types: localized(55537, 55657).into(),
+
+            // Item data
+            armor: localized(00007, 00076).into(),
+            armor2: localized(55548, 55668).into(),
+            currency: english_only(00091).map(Into::into),
+            general_items: localized(00004, 00073).into(),
+            general_items2: localized(55551, 55671).into(),
+            puppet_items: localized(00008, 00077).into(),
+            usable_items: localized(00005, 00074).into(),
+            weapons: localized(00006, 00075).into(),
+            vouchers_and_slips: localized(55547, 55667).into(),
+            monipulator: localized(55549, 55669).into(),
+            instincts: localized(55550, 55670).into(),
+
+            // Misc. data
+            // The menu data holds no text, and is the same for all languages
+            data_menu: 81.into(),
+            // Only one of the client fonts is mapped, and its id hasn't been checked against
+            // a retail client yet
+            font: english_only(82).map(Into::into),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{base::DatId, language::Language};
+
+    use super::DatIdMapping;
+
+    #[test]
+    pub fn distinct_dats() {
+        for language in [Language::Japanese, Language::English] {
+            let mapping = DatIdMapping::get(language);
+            let mut dats: Vec<(String, DatId)> = vec![];
+
+            macro_rules! single {
+                ($($field:ident),*) => {
+                    $( dats.push((stringify!($field).to_string(), (&mapping.$field).into())); )*
+                };
+            }
+            macro_rules! optional {
+                ($($field:ident),*) => {
+                    $( if let Some(dat) = &mapping.$field {
+                        dats.push((stringify!($field).to_string(), dat.into()));
+                    } )*
+                };
+            }
+            macro_rules! by_zone {
+                ($($field:ident),*) => {
+                    $( for (key, dat) in &mapping.$field.map {
+                        dats.push((format!("{} {:?}", stringify!($field), key), dat.into()));
+                    } )*
+                };
+            }
+
+            by_zone!(entities, dialog, dialog2, minimaps);
+            optional!(
+                monster_skill_names,
+                status_names_dialog,
+                emote_messages,
+                system_messages_1,
+                system_messages_2,
+                system_messages_3,
+                system_messages_4,
+                unity_dialogs,
+                status_info,
+                time_and_pronouns,
+                misc5,
+                misc6,
+                currency,
+                font
+            );
+            single!(
+                ability_names,
+                ability_descriptions,
+                area_names,
+                area_names_alt,
+                auto_translate,
+                character_select,
+                chat_filter_types,
+                day_names,
+                directions,
+                equipment_locations,
+                error_messages,
+                ingame_messages_1,
+                ingame_messages_2,
+                job_names,
+                key_items,
+                menu_items_description,
+                menu_items_text,
+                mission_log,
+                moon_phases,
+                pol_messages,
+                quest_log,
+                race_names,
+                region_names,
+                spell_names,
+                spell_descriptions,
+                status_names,
+                titles,
+                misc1,
+                misc2,
+                misc3,
+                misc4,
+                weather_types,
+                armor,
+                armor2,
+                general_items,
+                general_items2,
+                puppet_items,
+                usable_items,
+                weapons,
+                vouchers_and_slips,
+                monipulator,
+                instincts,
+                data_menu
+            );
 
-            Self {
-                entities,
-                dialog,
-                dialog2,
-
-                // Global dialog
-                monster_skill_names: 07035.into(),
-                status_names_dialog: 07029.into(),
-                emote_messages: 07025.into(),
-                system_messages_1: 07023.into(),
-                system_messages_2: 07031.into(),
-                system_messages_3: 07021.into(),
-                system_messages_4: 07027.into(),
-                unity_dialogs: 07039.into(),
-
-                // String tables
-                ability_names: 55701.into(),
-                ability_descriptions: 55733.into(),
-                area_names: 55465.into(),
-                area_names_alt: 55661.into(),
-                character_select: 55470.into(),
-                chat_filter_types: 55650.into(),
-                day_names: 55658.into(),
-                directions: 55659.into(),
-                equipment_locations: 55471.into(),
-                error_messages: 55646.into(),
-                ingame_messages_1: 55648.into(),
-                ingame_messages_2: 55649.into(),
-                job_names: 55467.into(),
-                key_items: 55695.into(),
-                menu_items_description: 55651.into(),
-                menu_items_text: 55652.into(),
-                moon_phases: 55660.into(),
-                pol_messages: 55647.into(),
-                race_names: 55469.into(),
-                region_names: 55654.into(),
-                spell_names: 55702.into(),
-                spell_descriptions: 55734.into(),
-                status_info: 00087.into(),
-                status_names: 55725.into(),
-                time_and_pronouns: 00063.into(),
-                titles: 55704.into(),
-                misc1: 55645.into(),
-                misc2: 55653.into(),
-                weather_types: 55657.into(),
-
-                // Item data
-                armor: 00076.into(),
-                armor2: 55668.into(),
-                currency: 00091.into(),
-                general_items: 00073.into(),
-                general_items2: 55671.into(),
-                puppet_items: 00077.into(),
-                usable_items: 00074.into(),
-                weapons: 00075.into(),
-                vouchers_and_slips: 55667.into(),
-                monipulator: 55669.into(),
-                instincts: 55670.into(),
-
-                // Misc. data
-                data_menu: 81.into(),
+            let mut owners = HashMap::new();
+            for (owner, dat_id) in dats {
+                if let Some(other) = owners.insert(dat_id, owner.clone()) {
+                    panic!("{language:?}: DAT {dat_id:?} is used for both {other} and {owner}.");
+                }
             }
-        })
+        }
     }
 }
@@ -1,12 +1,20 @@
-#![allow(dead_code)] // TODO: remove once images are in use
 use anyhow::{anyhow, Result};
-use common::byte_walker::ByteWalker;
+use base64::{engine::general_purpose, Engine as _};
+use common::{
+    byte_walker::{BufferedByteWalker, ByteWalker},
+    vec_byte_walker::VecByteWalker,
+    writing_byte_walker::WritingByteWalker,
+};
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+mod dxt;
+mod palette;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct XiImage {
     id: String,
     category: String,
+    format: XiImageFormat,
     width: u32,
     height: u32,
     planes: u16,
@@ -17,6 +25,64 @@ pub struct XiImage {
     vertical_resolution: u32,
     used_colors: u32,
     important_colors: u32,
+
+    /// Decoded pixels as RGBA, row by row from the top-left corner.
+    #[serde(skip)]
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DxtCompression {
+    Dxt1,
+    Dxt3,
+    Dxt5,
+}
+
+impl DxtCompression {
+    fn from_four_cc(four_cc: &str) -> Result<Self> {
+        match four_cc {
+            "DXT1" => Ok(DxtCompression::Dxt1),
+            "DXT3" => Ok(DxtCompression::Dxt3),
+            "DXT5" => Ok(DxtCompression::Dxt5),
+            _ => Err(anyhow!("Unsupported DirectX compression: \"{}\"", four_cc)),
+        }
+    }
+
+    fn four_cc(&self) -> &'static str {
+        match self {
+            DxtCompression::Dxt1 => "DXT1",
+            DxtCompression::Dxt3 => "DXT3",
+            DxtCompression::Dxt5 => "DXT5",
+        }
+    }
+
+    fn block_size(&self) -> usize {
+        match self {
+            DxtCompression::Dxt1 => 8,
+            DxtCompression::Dxt3 | DxtCompression::Dxt5 => 16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum XiImageFormat {
+    DirectX {
+        compression: DxtCompression,
+        unknown1: u64,
+    },
+    BitmapA,
+    BitmapB,
+}
+
+impl XiImageFormat {
+    fn type_byte(&self) -> u8 {
+        match self {
+            XiImageFormat::BitmapA => 0x91,
+            XiImageFormat::DirectX { .. } => 0xA1,
+            XiImageFormat::BitmapB => 0xB1,
+        }
+    }
 }
 
 pub enum XiImageType {
@@ -36,14 +102,284 @@ impl XiImageType {
     }
 }
 
+/// Keyword of the PNG text chunk that holds the original bytes of an exported image.
+const PNG_ORIGINAL_BYTES_KEYWORD: &str = "XiImage";
+
+const BITMAPINFO_SIZE: u32 = 40;
+const MAX_DIMENSION: u32 = 16 * 1024;
+
 impl XiImage {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::parse(&mut BufferedByteWalker::on(bytes))
+    }
+
+    /// Decodes the image bytes and encodes them as a PNG.
+    /// The original bytes are kept in the PNG, such that an unmodified image
+    /// can be written back exactly as it was.
+    pub fn bytes_to_png(bytes: &[u8]) -> Result<Vec<u8>> {
+        let image = Self::from_bytes(bytes)?;
+
+        let mut png_bytes = vec![];
+        let mut encoder = png::Encoder::new(&mut png_bytes, image.width, image.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.add_text_chunk(
+            PNG_ORIGINAL_BYTES_KEYWORD.to_string(),
+            general_purpose::STANDARD_NO_PAD.encode(bytes),
+        )?;
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&image.pixels)?;
+        writer.finish()?;
+
+        Ok(png_bytes)
+    }
+
+    /// Creates an image without pixels, which can be used as a template for encoding PNGs.
+    pub fn template(format: XiImageFormat, category: &str, id: &str, bit_count: u16) -> Self {
+        XiImage {
+            id: id.to_string(),
+            category: category.to_string(),
+            format,
+            width: 0,
+            height: 0,
+            planes: 1,
+            bit_count,
+            compression: 0,
+            image_size: 0,
+            horizontal_resolution: 0,
+            vertical_resolution: 0,
+            used_colors: 0,
+            important_colors: 0,
+            pixels: vec![],
+        }
+    }
+
+    /// Gets the image bytes for a PNG.
+    ///
+    /// If the PNG was created by `bytes_to_png` and hasn't been modified, the original bytes are
+    /// returned. Otherwise the PNG is encoded with the header and format of the original image,
+    /// or of `template` if the PNG doesn't contain the original image.
+    pub fn png_to_bytes(png_bytes: &[u8], template: &XiImage, max_size: usize) -> Result<Vec<u8>> {
+        let png = DecodedPng::decode(png_bytes)?;
+
+        let original = png
+            .original_bytes
+            .map(|bytes| Self::from_bytes(&bytes).map(|image| (bytes, image)))
+            .transpose()?;
+
+        if let Some((original_bytes, original)) = &original {
+            if original.width == png.width
+                && original.height == png.height
+                && original.pixels == png.pixels
+            {
+                return Ok(original_bytes.clone());
+            }
+        }
+
+        let template = original
+            .as_ref()
+            .map(|(_, image)| image)
+            .unwrap_or(template);
+
+        let bytes = template
+            .with_pixels(png.width, png.height, png.pixels)?
+            .to_bytes()?;
+
+        if bytes.len() > max_size {
+            return Err(anyhow!(
+                "Encoded image is {} bytes, but at most {} bytes can fit.",
+                bytes.len(),
+                max_size
+            ));
+        }
+
+        Ok(bytes)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Creates a copy of the image with other pixels.
+    pub fn with_pixels(&self, width: u32, height: u32, pixels: Vec<u8>) -> Result<Self> {
+        if width == 0 || height == 0 || width > MAX_DIMENSION || height > MAX_DIMENSION {
+            return Err(anyhow!("Invalid image dimensions: {}x{}", width, height));
+        }
+
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(anyhow!(
+                "Expected {} bytes of RGBA pixels for {}x{}, but got {}.",
+                width as usize * height as usize * 4,
+                width,
+                height,
+                pixels.len()
+            ));
+        }
+
+        if matches!(self.format, XiImageFormat::DirectX { .. })
+            && (width % 4 != 0 || height % 4 != 0)
+        {
+            return Err(anyhow!(
+                "DirectX images must have a width and height that are a multiple of 4, but got {}x{}.",
+                width,
+                height
+            ));
+        }
+
+        Ok(XiImage {
+            width,
+            height,
+            pixels,
+            ..self.clone()
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut walker = VecByteWalker::new();
+        self.write(&mut walker)?;
+        Ok(walker.into_vec())
+    }
+
+    pub(crate) fn write<T: WritingByteWalker>(&self, walker: &mut T) -> Result<()> {
+        let pixel_bytes = match self.format {
+            XiImageFormat::DirectX { compression, .. } => self.encode_directx(compression),
+            XiImageFormat::BitmapA | XiImageFormat::BitmapB => self.encode_bitmap()?,
+        };
+
+        walker.write(self.format.type_byte());
+
+        for (name, value) in [("category", &self.category), ("id", &self.id)] {
+            if value.len() != 8 || !value.is_ascii() {
+                return Err(anyhow!(
+                    "Image {} has to be 8 ASCII characters, but got \"{}\"",
+                    name,
+                    value
+                ));
+            }
+            walker.write_str(value);
+        }
+
+        walker.write(BITMAPINFO_SIZE);
+        walker.write(self.width);
+        walker.write(self.height);
+        walker.write(self.planes);
+        walker.write(self.bit_count);
+        walker.write(self.compression);
+        // Like in a BITMAPINFOHEADER, the size leaves out the palette. Images that don't
+        // have a size, like the retail status icons, are kept that way.
+        walker.write(if self.image_size != 0 {
+            (pixel_bytes.len() - self.palette_size()) as u32
+        } else {
+            0
+        });
+        walker.write(self.horizontal_resolution);
+        walker.write(self.vertical_resolution);
+        walker.write(self.used_colors);
+        walker.write(self.important_colors);
+
+        if let XiImageFormat::DirectX {
+            compression,
+            unknown1,
+        } = self.format
+        {
+            walker.write_str(compression.four_cc());
+            walker.write(unknown1);
+        }
+
+        walker.write_bytes(&pixel_bytes);
+
+        Ok(())
+    }
+
+    fn palette_size(&self) -> usize {
+        match (self.format, self.bit_count) {
+            (XiImageFormat::BitmapA | XiImageFormat::BitmapB, 4 | 8) => 4 << self.bit_count,
+            _ => 0,
+        }
+    }
+
+    fn encode_directx(&self, compression: DxtCompression) -> Vec<u8> {
+        let width = self.width as usize;
+        let blocks_wide = width / 4;
+        let blocks_high = self.height as usize / 4;
+
+        let mut bytes = Vec::with_capacity(blocks_wide * blocks_high * compression.block_size());
+        for block_idx in 0..blocks_wide * blocks_high {
+            let block_x = (block_idx % blocks_wide) * 4;
+            let block_y = (block_idx / blocks_wide) * 4;
+
+            let mut texels = [[0u8; 4]; 16];
+            for (texel_idx, texel) in texels.iter_mut().enumerate() {
+                let x = block_x + texel_idx % 4;
+                let y = block_y + texel_idx / 4;
+                let pixel_offset = (y * width + x) * 4;
+                texel.copy_from_slice(&self.pixels[pixel_offset..pixel_offset + 4]);
+            }
+
+            bytes.extend_from_slice(&dxt::encode_block(compression, &texels));
+        }
+
+        bytes
+    }
+
+    fn encode_bitmap(&self) -> Result<Vec<u8>> {
+        // Bitmap rows are stored from the bottom up.
+        let bottom_up_pixels = self
+            .pixels
+            .chunks(self.width as usize * 4)
+            .rev()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut bytes = vec![];
+        match self.bit_count {
+            4 | 8 => {
+                // Images with more colors than the palette can hold are reduced to fit.
+                let max_colors = 1usize << self.bit_count;
+                let (palette, indices) = palette::palette_indices(&bottom_up_pixels, max_colors);
+
+                for idx in 0..max_colors {
+                    let rgba = palette.get(idx).copied().unwrap_or([0, 0, 0, 0]);
+                    bytes.extend_from_slice(&[rgba[2], rgba[1], rgba[0], rgba[3]]);
+                }
+
+                if self.bit_count == 8 {
+                    bytes.extend(indices.iter().map(|idx| *idx as u8));
+                } else {
+                    bytes.extend(indices.chunks(2).map(|pair| {
+                        ((pair[0] as u8) << 4) | pair.get(1).map(|idx| *idx as u8).unwrap_or(0)
+                    }));
+                }
+            }
+            32 => {
+                for rgba in bottom_up_pixels.chunks(4) {
+                    bytes.extend_from_slice(&[rgba[2], rgba[1], rgba[0], rgba[3]]);
+                }
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported bit count for bitmap: {}",
+                    self.bit_count
+                ));
+            }
+        }
+
+        Ok(bytes)
+    }
+
     pub(crate) fn parse<T: ByteWalker>(walker: &mut T) -> Result<Self> {
         let image_type = XiImageType::from(walker.step::<u8>()?)?;
 
         let category = std::str::from_utf8(walker.take_bytes(8)?)?.to_string();
         let id = std::str::from_utf8(walker.take_bytes(8)?)?.to_string();
 
-        walker.expect_msg(40, "BITMAPINFO structure length")?;
+        walker.expect_msg(BITMAPINFO_SIZE, "BITMAPINFO structure length")?;
 
         let width = walker.step::<u32>()?;
         let height = walker.step::<u32>()?;
@@ -56,19 +392,26 @@ impl XiImage {
         let used_colors = walker.step::<u32>()?;
         let important_colors = walker.step::<u32>()?;
 
-        if width > 16 * 1024 || height > 16 * 1024 || planes != 1 {
+        if width > MAX_DIMENSION || height > MAX_DIMENSION || planes != 1 {
             return Err(anyhow!("Incompatible width, height, or planes in image."));
         }
 
-        match image_type {
+        let (format, pixels) = match image_type {
             XiImageType::DirectX => Self::parse_directx(walker, width, height)?,
-            XiImageType::BitmapA => todo!(),
-            XiImageType::BitmapB => todo!(),
-        }
+            XiImageType::BitmapA => (
+                XiImageFormat::BitmapA,
+                Self::parse_bitmap(walker, width, height, bit_count)?,
+            ),
+            XiImageType::BitmapB => (
+                XiImageFormat::BitmapB,
+                Self::parse_bitmap(walker, width, height, bit_count)?,
+            ),
+        };
 
         Ok(XiImage {
             id,
             category,
+            format,
             width,
             height,
             planes,
@@ -79,10 +422,15 @@ impl XiImage {
             vertical_resolution,
             used_colors,
             important_colors,
+            pixels,
         })
     }
 
-    fn parse_directx<T: ByteWalker>(walker: &mut T, width: u32, height: u32) -> Result<()> {
+    fn parse_directx<T: ByteWalker>(
+        walker: &mut T,
+        width: u32,
+        height: u32,
+    ) -> Result<(XiImageFormat, Vec<u8>)> {
         let four_cc = std::str::from_utf8(walker.take_bytes(4)?)?;
 
         if !four_cc.starts_with("DXT") {
@@ -91,6 +439,7 @@ impl XiImage {
                 &four_cc[..3]
             ));
         }
+        let compression = DxtCompression::from_four_cc(four_cc)?;
 
         if width % 4 != 0 || height % 4 != 0 {
             return Err(anyhow!(
@@ -100,9 +449,281 @@ impl XiImage {
             ));
         }
 
-        let _texel_block_count = height / 4 * width / 4;
-        let _unknown1 = walker.step::<u64>()?;
+        let unknown1 = walker.step::<u64>()?;
+
+        let blocks_wide = width as usize / 4;
+        let blocks_high = height as usize / 4;
+        let block_bytes =
+            walker.take_bytes(blocks_wide * blocks_high * compression.block_size())?;
+
+        let mut pixels = vec![0u8; width as usize * height as usize * 4];
+        for (block_idx, block) in block_bytes.chunks(compression.block_size()).enumerate() {
+            let texels = dxt::decode_block(compression, block);
+
+            let block_x = (block_idx % blocks_wide) * 4;
+            let block_y = (block_idx / blocks_wide) * 4;
+            for (texel_idx, texel) in texels.iter().enumerate() {
+                let x = block_x + texel_idx % 4;
+                let y = block_y + texel_idx / 4;
+                let pixel_offset = (y * width as usize + x) * 4;
+                pixels[pixel_offset..pixel_offset + 4].copy_from_slice(texel);
+            }
+        }
+
+        Ok((
+            XiImageFormat::DirectX {
+                compression,
+                unknown1,
+            },
+            pixels,
+        ))
+    }
+}
+
+impl XiImage {
+    fn parse_bitmap<T: ByteWalker>(
+        walker: &mut T,
+        width: u32,
+        height: u32,
+        bit_count: u16,
+    ) -> Result<Vec<u8>> {
+        let pixel_count = width as usize * height as usize;
+
+        // Colors are stored as BGRA, both in the palette and for 32-bit pixels.
+        let mut bottom_up_pixels = Vec::with_capacity(pixel_count * 4);
+        match bit_count {
+            4 | 8 => {
+                let palette = walker
+                    .take_bytes(4 * (1 << bit_count))?
+                    .chunks(4)
+                    .map(|bgra| [bgra[2], bgra[1], bgra[0], bgra[3]])
+                    .collect::<Vec<_>>();
+
+                let indices: Vec<usize> = if bit_count == 8 {
+                    walker
+                        .take_bytes(pixel_count)?
+                        .iter()
+                        .map(|idx| *idx as usize)
+                        .collect()
+                } else {
+                    walker
+                        .take_bytes(pixel_count.div_ceil(2))?
+                        .iter()
+                        .flat_map(|byte| [(byte >> 4) as usize, (byte & 0x0F) as usize])
+                        .take(pixel_count)
+                        .collect()
+                };
+
+                for idx in indices {
+                    bottom_up_pixels.extend_from_slice(&palette[idx]);
+                }
+            }
+            32 => {
+                for bgra in walker.take_bytes(pixel_count * 4)?.chunks(4) {
+                    bottom_up_pixels.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+                }
+            }
+            _ => {
+                return Err(anyhow!("Unsupported bit count for bitmap: {}", bit_count));
+            }
+        }
+
+        // Bitmap rows are stored from the bottom up.
+        let row_bytes = width as usize * 4;
+        if row_bytes == 0 {
+            return Ok(bottom_up_pixels);
+        }
+        Ok(bottom_up_pixels
+            .chunks(row_bytes)
+            .rev()
+            .flatten()
+            .copied()
+            .collect())
+    }
+}
+
+struct DecodedPng {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    original_bytes: Option<Vec<u8>>,
+}
+
+impl DecodedPng {
+    fn decode(png_bytes: &[u8]) -> Result<Self> {
+        let mut decoder = png::Decoder::new(png_bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+
+        let original_bytes = reader
+            .info()
+            .uncompressed_latin1_text
+            .iter()
+            .find(|chunk| chunk.keyword == PNG_ORIGINAL_BYTES_KEYWORD)
+            .map(|chunk| general_purpose::STANDARD_NO_PAD.decode(chunk.text.as_bytes()))
+            .transpose()?;
+
+        let mut buffer = vec![0u8; reader.output_buffer_size()];
+        let output_info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(output_info.buffer_size());
+
+        // Convert all color types to RGBA
+        let pixels = match output_info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 0xFF])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks(2)
+                .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|g| [*g, *g, *g, 0xFF]).collect(),
+            png::ColorType::Indexed => {
+                return Err(anyhow!("Indexed PNG colors were not expanded."));
+            }
+        };
+
+        Ok(DecodedPng {
+            width: output_info.width,
+            height: output_info.height,
+            pixels,
+            original_bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use common::byte_walker::BufferedByteWalker;
+
+    use super::{DxtCompression, XiImage, XiImageFormat};
+
+    fn bitmap_image_bytes() -> Vec<u8> {
+        let mut bytes = vec![0x91];
+        bytes.extend_from_slice(b"menu    ");
+        bytes.extend_from_slice(b"item    ");
+        for value in [40u32, 2, 2] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&32u16.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 24]);
+        for idx in 0..4u8 {
+            bytes.extend_from_slice(&[idx, 0x10 * idx, 0xFF, 0x80]);
+        }
+        bytes
+    }
+
+    #[test]
+    pub fn png_roundtrip() {
+        let bytes = bitmap_image_bytes();
+        let png_bytes = XiImage::bytes_to_png(&bytes).unwrap();
+
+        let template = XiImage::template(XiImageFormat::BitmapA, "menu    ", "item    ", 8);
+        assert_eq!(
+            XiImage::png_to_bytes(&png_bytes, &template, bytes.len()).unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    pub fn bitmap_reencode() {
+        let bytes = bitmap_image_bytes();
+        let image = XiImage::from_bytes(&bytes).unwrap();
+
+        assert_eq!(image.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    pub fn bitmap_8bit_encode() {
+        let template = XiImage::template(XiImageFormat::BitmapA, "menu    ", "item    ", 8);
+        let pixels = [[0xFF, 0x00, 0x00, 0x80], [0x00, 0xFF, 0x00, 0x80]].concat();
+        let image = template.with_pixels(2, 1, pixels.clone()).unwrap();
+
+        let decoded = XiImage::from_bytes(&image.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.pixels, pixels);
+    }
+
+    #[test]
+    pub fn bitmap_too_many_colors() {
+        let template = XiImage::template(XiImageFormat::BitmapA, "menu    ", "item    ", 4);
+        let pixels = (0..32u8)
+            .flat_map(|idx| [idx * 8, 0x40, 0x80, 0xFF])
+            .collect::<Vec<_>>();
+
+        let image = template.with_pixels(8, 4, pixels.clone()).unwrap();
+        let decoded = XiImage::from_bytes(&image.to_bytes().unwrap()).unwrap();
+
+        // Pairs of neighboring reds share a palette color.
+        let colors = decoded.pixels.chunks(4).collect::<HashSet<_>>();
+        assert_eq!(colors.len(), 16);
+        for (decoded, original) in decoded.pixels.chunks(4).zip(pixels.chunks(4)) {
+            assert!(decoded[0].abs_diff(original[0]) <= 4);
+            assert_eq!(decoded[1..], original[1..]);
+        }
+    }
+
+    #[test]
+    pub fn bitmap_image_size() {
+        let mut image = XiImage::template(XiImageFormat::BitmapA, "menu    ", "item    ", 8)
+            .with_pixels(2, 2, [0x20, 0x40, 0x60, 0xFF].repeat(4))
+            .unwrap();
+        assert_eq!(image.to_bytes().unwrap()[37..41], [0; 4]);
+
+        // Only the 4 pixel indices are counted, not the palette.
+        image.image_size = 1;
+        assert_eq!(image.to_bytes().unwrap()[37..41], 4u32.to_le_bytes());
+    }
+
+    #[test]
+    pub fn directx_encode() {
+        let template = XiImage::template(
+            XiImageFormat::DirectX {
+                compression: DxtCompression::Dxt3,
+                unknown1: 0,
+            },
+            "menu    ",
+            "icon    ",
+            32,
+        );
+        let pixels = [0x00, 0xFF, 0x00, 0xFF].repeat(8 * 4);
+        let image = template.with_pixels(8, 4, pixels.clone()).unwrap();
+
+        let decoded = XiImage::from_bytes(&image.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.pixels, pixels);
+
+        assert!(template.with_pixels(6, 4, vec![0; 6 * 4 * 4]).is_err());
+    }
+
+    #[test]
+    pub fn bitmap_4bit() {
+        let mut bytes = vec![];
+        // Palette with red as index 0 and blue as index 1, as BGRA
+        bytes.extend_from_slice(&[0x00, 0x00, 0xFF, 0x80]);
+        bytes.extend_from_slice(&[0xFF, 0x00, 0x00, 0x80]);
+        bytes.extend_from_slice(&[0u8; 4 * 14]);
+        // 2x2 pixels, with the bottom row first
+        bytes.extend_from_slice(&[0x01, 0x10]);
+
+        let pixels = XiImage::parse_bitmap(&mut BufferedByteWalker::on(&bytes), 2, 2, 4).unwrap();
+
+        assert_eq!(
+            pixels,
+            vec![
+                0x00, 0x00, 0xFF, 0x80, 0xFF, 0x00, 0x00, 0x80, // Top row
+                0xFF, 0x00, 0x00, 0x80, 0x00, 0x00, 0xFF, 0x80, // Bottom row
+            ]
+        );
+    }
+
+    #[test]
+    pub fn bitmap_invalid() {
+        let bytes = [0u8; 8];
 
-        todo!();
+        assert!(XiImage::parse_bitmap(&mut BufferedByteWalker::on(&bytes), 2, 2, 8).is_err());
+        assert!(XiImage::parse_bitmap(&mut BufferedByteWalker::on(&bytes), 2, 2, 16).is_err());
     }
 }
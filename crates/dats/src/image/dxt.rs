@@ -0,0 +1,292 @@
+use super::DxtCompression;
+
+type Texels = [[u8; 4]; 16];
+
+pub(super) fn decode_block(compression: DxtCompression, block: &[u8]) -> Texels {
+    match compression {
+        DxtCompression::Dxt1 => decode_dxt1_block(block),
+        DxtCompression::Dxt3 => decode_dxt3_block(block),
+        DxtCompression::Dxt5 => decode_dxt5_block(block),
+    }
+}
+
+pub(super) fn encode_block(compression: DxtCompression, texels: &Texels) -> Vec<u8> {
+    match compression {
+        DxtCompression::Dxt1 => encode_dxt1_block(texels).to_vec(),
+        DxtCompression::Dxt3 => encode_dxt3_block(texels).to_vec(),
+        DxtCompression::Dxt5 => encode_dxt5_block(texels).to_vec(),
+    }
+}
+
+fn rgb565_to_rgba(color: u16) -> [u8; 4] {
+    let r = ((color >> 11) & 0x1F) as u8;
+    let g = ((color >> 5) & 0x3F) as u8;
+    let b = (color & 0x1F) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+        0xFF,
+    ]
+}
+
+fn rgba_to_rgb565(color: [u8; 4]) -> u16 {
+    ((color[0] as u16 >> 3) << 11) | ((color[1] as u16 >> 2) << 5) | (color[2] as u16 >> 3)
+}
+
+fn interpolate(a: [u8; 4], b: [u8; 4], weight_a: u16, weight_b: u16) -> [u8; 4] {
+    let total = weight_a + weight_b;
+    let mut color = [0u8; 4];
+    for idx in 0..4 {
+        color[idx] = ((a[idx] as u16 * weight_a + b[idx] as u16 * weight_b) / total) as u8;
+    }
+    color
+}
+
+fn color_distance(a: [u8; 4], b: [u8; 4]) -> u32 {
+    (0..3)
+        .map(|idx| (a[idx] as i32 - b[idx] as i32).pow(2) as u32)
+        .sum()
+}
+
+/// The colors that the indices of a DXT color block refer to.
+/// The 3-color mode with transparency is only available for DXT1.
+fn color_palette(color0: u16, color1: u16, allow_transparency: bool) -> [[u8; 4]; 4] {
+    let rgba0 = rgb565_to_rgba(color0);
+    let rgba1 = rgb565_to_rgba(color1);
+
+    if color0 > color1 || !allow_transparency {
+        [
+            rgba0,
+            rgba1,
+            interpolate(rgba0, rgba1, 2, 1),
+            interpolate(rgba0, rgba1, 1, 2),
+        ]
+    } else {
+        [rgba0, rgba1, interpolate(rgba0, rgba1, 1, 1), [0, 0, 0, 0]]
+    }
+}
+
+/// The alpha values that the indices of a DXT5 alpha block refer to.
+fn alpha_palette(alpha0: u8, alpha1: u8) -> [u8; 8] {
+    let alpha0 = alpha0 as u16;
+    let alpha1 = alpha1 as u16;
+
+    let mut palette = [0u8; 8];
+    palette[0] = alpha0 as u8;
+    palette[1] = alpha1 as u8;
+    if alpha0 > alpha1 {
+        for idx in 1..7 {
+            palette[idx + 1] = (((7 - idx as u16) * alpha0 + idx as u16 * alpha1) / 7) as u8;
+        }
+    } else {
+        for idx in 1..5 {
+            palette[idx + 1] = (((5 - idx as u16) * alpha0 + idx as u16 * alpha1) / 5) as u8;
+        }
+        palette[6] = 0x00;
+        palette[7] = 0xFF;
+    }
+    palette
+}
+
+/// Decodes the 8-byte color part of a DXT block into 16 RGBA texels.
+fn decode_color_block(block: &[u8], allow_transparency: bool) -> Texels {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let palette = color_palette(color0, color1, allow_transparency);
+
+    let mut texels = [[0u8; 4]; 16];
+    for (idx, texel) in texels.iter_mut().enumerate() {
+        *texel = palette[((indices >> (2 * idx)) & 0b11) as usize];
+    }
+    texels
+}
+
+fn decode_dxt1_block(block: &[u8]) -> Texels {
+    decode_color_block(block, true)
+}
+
+fn decode_dxt3_block(block: &[u8]) -> Texels {
+    let alphas = u64::from_le_bytes(block[0..8].try_into().unwrap());
+    let mut texels = decode_color_block(&block[8..16], false);
+    for (idx, texel) in texels.iter_mut().enumerate() {
+        let alpha = ((alphas >> (4 * idx)) & 0x0F) as u8;
+        texel[3] = (alpha << 4) | alpha;
+    }
+    texels
+}
+
+fn decode_dxt5_block(block: &[u8]) -> Texels {
+    let palette = alpha_palette(block[0], block[1]);
+
+    let mut alpha_index_bytes = [0u8; 8];
+    alpha_index_bytes[..6].copy_from_slice(&block[2..8]);
+    let alpha_indices = u64::from_le_bytes(alpha_index_bytes);
+
+    let mut texels = decode_color_block(&block[8..16], false);
+    for (idx, texel) in texels.iter_mut().enumerate() {
+        texel[3] = palette[((alpha_indices >> (3 * idx)) & 0b111) as usize];
+    }
+    texels
+}
+
+/// Encodes 16 RGBA texels into the 8-byte color part of a DXT block, using the bounding box
+/// of the colors as end points. Texels with less than half alpha are made transparent,
+/// if the 3-color mode is allowed.
+fn encode_color_block(texels: &Texels, allow_transparency: bool) -> [u8; 8] {
+    let use_transparency = allow_transparency && texels.iter().any(|texel| texel[3] < 0x80);
+    let is_transparent = |texel: &[u8; 4]| use_transparency && texel[3] < 0x80;
+
+    let mut min = [0xFFu8; 4];
+    let mut max = [0x00u8; 4];
+    for texel in texels.iter().filter(|texel| !is_transparent(texel)) {
+        for idx in 0..3 {
+            min[idx] = min[idx].min(texel[idx]);
+            max[idx] = max[idx].max(texel[idx]);
+        }
+    }
+    if min[0] > max[0] {
+        // All texels are transparent
+        min = [0; 4];
+        max = [0; 4];
+    }
+
+    // Since every component of max is at least the one in min, so is the combined 565 value.
+    let max_565 = rgba_to_rgb565(max);
+    let min_565 = rgba_to_rgb565(min);
+    let (color0, color1) = if use_transparency {
+        (min_565, max_565)
+    } else {
+        (max_565, min_565)
+    };
+
+    let palette = color_palette(color0, color1, allow_transparency);
+    let usable_colors = if use_transparency { 3 } else { 4 };
+
+    let mut indices = 0u32;
+    for (idx, texel) in texels.iter().enumerate() {
+        let palette_idx = if is_transparent(texel) {
+            3
+        } else if color0 == color1 {
+            0
+        } else {
+            (0..usable_colors)
+                .min_by_key(|palette_idx| color_distance(palette[*palette_idx], *texel))
+                .unwrap()
+        };
+        indices |= (palette_idx as u32) << (2 * idx);
+    }
+
+    let mut block = [0u8; 8];
+    block[0..2].copy_from_slice(&color0.to_le_bytes());
+    block[2..4].copy_from_slice(&color1.to_le_bytes());
+    block[4..8].copy_from_slice(&indices.to_le_bytes());
+    block
+}
+
+fn encode_dxt1_block(texels: &Texels) -> [u8; 8] {
+    encode_color_block(texels, true)
+}
+
+fn encode_dxt3_block(texels: &Texels) -> [u8; 16] {
+    let mut alphas = 0u64;
+    for (idx, texel) in texels.iter().enumerate() {
+        alphas |= ((texel[3] >> 4) as u64) << (4 * idx);
+    }
+
+    let mut block = [0u8; 16];
+    block[0..8].copy_from_slice(&alphas.to_le_bytes());
+    block[8..16].copy_from_slice(&encode_color_block(texels, false));
+    block
+}
+
+fn encode_dxt5_block(texels: &Texels) -> [u8; 16] {
+    let alpha0 = texels.iter().map(|texel| texel[3]).max().unwrap();
+    let alpha1 = texels.iter().map(|texel| texel[3]).min().unwrap();
+    let palette = alpha_palette(alpha0, alpha1);
+
+    let mut alpha_indices = 0u64;
+    for (idx, texel) in texels.iter().enumerate() {
+        let palette_idx = (0..8)
+            .min_by_key(|palette_idx| palette[*palette_idx].abs_diff(texel[3]))
+            .unwrap();
+        alpha_indices |= (palette_idx as u64) << (3 * idx);
+    }
+
+    let mut block = [0u8; 16];
+    block[0] = alpha0;
+    block[1] = alpha1;
+    block[2..8].copy_from_slice(&alpha_indices.to_le_bytes()[..6]);
+    block[8..16].copy_from_slice(&encode_color_block(texels, false));
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_dxt1_block, decode_dxt3_block, decode_dxt5_block, encode_dxt1_block,
+        encode_dxt3_block, encode_dxt5_block, Texels,
+    };
+
+    #[test]
+    pub fn dxt1_block() {
+        // Red and blue endpoints, with texels alternating between all four palette entries.
+        let block = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+        let texels = decode_dxt1_block(&block);
+
+        assert_eq!(texels[0], [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(texels[1], [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(texels[2], [0xAA, 0x00, 0x55, 0xFF]);
+        assert_eq!(texels[3], [0x55, 0x00, 0xAA, 0xFF]);
+    }
+
+    #[test]
+    pub fn dxt1_block_transparent() {
+        // color0 <= color1 enables the 3-color mode, where index 3 is transparent.
+        let block = [0x1F, 0x00, 0x00, 0xF8, 0xFF, 0xFF, 0xFF, 0xFF];
+        let texels = decode_dxt1_block(&block);
+
+        assert_eq!(texels[15], [0x00, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    pub fn dxt1_encode_transparent() {
+        let mut texels: Texels = [[0xFF, 0x00, 0x00, 0xFF]; 16];
+        texels[5] = [0x00, 0x00, 0x00, 0x00];
+
+        let decoded = decode_dxt1_block(&encode_dxt1_block(&texels));
+
+        assert_eq!(decoded[0], [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(decoded[5], [0x00, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    pub fn dxt3_block() {
+        let mut block = [0u8; 16];
+        block[0] = 0xF0; // First texel alpha 0x0, second 0xF
+        block[8..16].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00]);
+        let texels = decode_dxt3_block(&block);
+
+        assert_eq!(texels[0], [0xFF, 0xFF, 0xFF, 0x00]);
+        assert_eq!(texels[1], [0xFF, 0xFF, 0xFF, 0xFF]);
+
+        assert_eq!(decode_dxt3_block(&encode_dxt3_block(&texels)), texels);
+    }
+
+    #[test]
+    pub fn dxt5_block() {
+        let mut block = [0u8; 16];
+        block[0] = 0xFF;
+        block[1] = 0x00;
+        block[2] = 0b0000_1001; // First texel index 1, second texel index 1
+        let texels = decode_dxt5_block(&block);
+
+        assert_eq!(texels[0][3], 0x00);
+        assert_eq!(texels[1][3], 0x00);
+        assert_eq!(texels[2][3], 0xFF);
+
+        assert_eq!(decode_dxt5_block(&encode_dxt5_block(&texels)), texels);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+type Color = [u8; 4];
+
+/// Picks at most `max_colors` palette colors for RGBA pixels, along with the palette index of
+/// each pixel. Colors are kept as-is in the order they're first used if they fit, and are
+/// otherwise reduced with median cut.
+pub(super) fn palette_indices(pixels: &[u8], max_colors: usize) -> (Vec<Color>, Vec<usize>) {
+    let colors = pixels
+        .chunks(4)
+        .map(|rgba| [rgba[0], rgba[1], rgba[2], rgba[3]])
+        .collect::<Vec<_>>();
+
+    let mut palette = vec![];
+    let mut color_indices = HashMap::new();
+    for color in &colors {
+        color_indices.entry(*color).or_insert_with(|| {
+            palette.push(*color);
+            palette.len() - 1
+        });
+    }
+
+    if palette.len() > max_colors {
+        let mut counts = HashMap::new();
+        for color in &colors {
+            *counts.entry(*color).or_insert(0usize) += 1;
+        }
+
+        let boxes = median_cut(
+            palette
+                .iter()
+                .map(|color| (*color, counts[color]))
+                .collect(),
+            max_colors,
+        );
+
+        palette = boxes.iter().map(|colors| average_color(colors)).collect();
+        for (idx, colors) in boxes.iter().enumerate() {
+            for (color, _) in colors {
+                color_indices.insert(*color, idx);
+            }
+        }
+    }
+
+    let indices = colors.iter().map(|color| color_indices[color]).collect();
+    (palette, indices)
+}
+
+/// Splits the colors into at most `max_boxes` boxes, by repeatedly splitting the box with the
+/// widest range in a channel at the median of that channel, weighted by pixel count.
+fn median_cut(colors: Vec<(Color, usize)>, max_boxes: usize) -> Vec<Vec<(Color, usize)>> {
+    let mut boxes = vec![colors];
+
+    while boxes.len() < max_boxes {
+        let Some((box_idx, channel, _)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(idx, colors)| {
+                let (channel, range) = widest_channel(colors);
+                (idx, channel, range)
+            })
+            .max_by_key(|(_, _, range)| *range)
+        else {
+            break;
+        };
+
+        let mut lower = boxes.swap_remove(box_idx);
+        lower.sort_unstable_by_key(|(color, _)| color[channel]);
+
+        let total = lower.iter().map(|(_, count)| count).sum::<usize>();
+        let mut seen = 0;
+        let median = lower
+            .iter()
+            .position(|(_, count)| {
+                seen += count;
+                seen * 2 >= total
+            })
+            .unwrap_or(0);
+
+        // Both halves keep at least one color.
+        let upper = lower.split_off((median + 1).min(lower.len() - 1));
+        boxes.push(lower);
+        boxes.push(upper);
+    }
+
+    boxes
+}
+
+fn widest_channel(colors: &[(Color, usize)]) -> (usize, u8) {
+    (0..4)
+        .map(|channel| {
+            let values = colors.iter().map(|(color, _)| color[channel]);
+            let range = values.clone().max().unwrap_or(0) - values.min().unwrap_or(0);
+            (channel, range)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap()
+}
+
+fn average_color(colors: &[(Color, usize)]) -> Color {
+    let total = colors.iter().map(|(_, count)| count).sum::<usize>();
+
+    let mut average = [0; 4];
+    for (channel, value) in average.iter_mut().enumerate() {
+        let sum = colors
+            .iter()
+            .map(|(color, count)| color[channel] as usize * count)
+            .sum::<usize>();
+        *value = ((sum + total / 2) / total) as u8;
+    }
+
+    average
+}
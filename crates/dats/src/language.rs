@@ -0,0 +1,24 @@
+use serde_derive::{Deserialize, Serialize};
+
+/// Language of the client text that is worked on, numbered like the client does.
+/// The same content is kept in different DATs for each language. French and German
+/// are left out until their DATs are mapped.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    specta::Type,
+)]
+pub enum Language {
+    Japanese = 0,
+    #[default]
+    English = 1,
+}
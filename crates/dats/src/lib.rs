@@ -1,11 +1,15 @@
+pub mod audio;
 pub mod base;
+pub mod chunk;
 pub mod context;
 pub mod dat_format;
 pub mod enums;
+pub mod external_files;
 pub mod flags;
 pub mod formats;
 pub mod id_mapping;
 pub mod image;
+pub mod language;
 pub mod sanitize_filename;
 mod serde_base64;
 mod serde_flags;
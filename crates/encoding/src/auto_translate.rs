@@ -0,0 +1,49 @@
+use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
+
+/// Resource type byte of auto-translate references, as in `FD 02 <language> <category> <phrase> FD`.
+pub const AUTO_TRANSLATE_TYPE: u8 = 0x02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AutoTranslateId {
+    pub language: u8,
+    pub category: u8,
+    pub phrase: u8,
+}
+
+impl AutoTranslateId {
+    pub fn to_bytes(&self) -> [u8; 4] {
+        [
+            AUTO_TRANSLATE_TYPE,
+            self.language,
+            self.category,
+            self.phrase,
+        ]
+    }
+}
+
+/// Phrase texts from the auto-translate dictionary by their IDs.
+pub type AutoTranslatePhrases = BTreeMap<AutoTranslateId, String>;
+
+thread_local! {
+    static PHRASES: RefCell<Option<Arc<AutoTranslatePhrases>>> = const { RefCell::new(None) };
+}
+
+/// Restores the phrases that were in use before, also when decoding panics.
+struct PhrasesGuard(Option<Arc<AutoTranslatePhrases>>);
+
+impl Drop for PhrasesGuard {
+    fn drop(&mut self) {
+        PHRASES.with(|phrases| *phrases.borrow_mut() = self.0.take());
+    }
+}
+
+/// Decodes with the phrase texts shown next to auto-translate references. Text that is
+/// decoded outside of this only has the IDs of the references.
+pub fn with_phrases<R>(phrases: Arc<AutoTranslatePhrases>, decode: impl FnOnce() -> R) -> R {
+    let _guard = PhrasesGuard(PHRASES.with(|current| current.replace(Some(phrases))));
+    decode()
+}
+
+pub(crate) fn phrase_text(id: &AutoTranslateId) -> Option<String> {
+    PHRASES.with(|phrases| phrases.borrow().as_ref()?.get(id).cloned())
+}
@@ -20,6 +20,11 @@ impl ConversionTable {
         )
     }
 
+    /// Whether the byte starts a character of two bytes, which is looked up in the table of the byte.
+    pub fn is_lead_byte(byte: u8) -> bool {
+        Self::lookup(0, byte) == 0xFFFE
+    }
+
     pub fn rev_lookup(input: u16) -> u16 {
         REVERSE_TABLE
             .get_or_init(|| {
@@ -87,6 +92,15 @@ impl ConversionTable {
             .unwrap_or_default()
     }
 
+    /// The bytes a UTF-16 character is encoded as, or no bytes if it's not in the tables.
+    pub fn rev_lookup_bytes(input: u16) -> Vec<u8> {
+        Self::rev_lookup(input)
+            .to_le_bytes()
+            .into_iter()
+            .filter(|byte| *byte != 0)
+            .collect()
+    }
+
     #[inline]
     pub fn get_table(table: u8) -> &'static [u8] {
         match table {
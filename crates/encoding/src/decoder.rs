@@ -1,10 +1,11 @@
 use std::char::decode_utf16;
 
 use crate::{
+    auto_translate::{phrase_text, AutoTranslateId, AUTO_TRANSLATE_TYPE},
     conversion_tables::ConversionTable,
     encoder::Encoder,
     named_bytes::{base_len_1, icon, prefix_01, prefix_7f_len_1},
-    SPACE_U16, TAG_END_U16, TAG_PARAM_START_U16, TAG_PREFIX_U16, TAG_START_U16,
+    SPACE_U16, TAG_END, TAG_END_U16, TAG_PARAM_START_U16, TAG_PREFIX_U16, TAG_START_U16,
 };
 use anyhow::Result;
 
@@ -117,8 +118,12 @@ impl<'a> Decoder<'a> {
             }
 
             if byte == 0xFD && self.can_extend(5) && self.get_at_offset(5) == 0xFD {
-                // TODO: Resource bytes?
-                self.make_hex_bytes_tag("resource", &self.source_bytes[self.idx..self.idx + 5]);
+                if self.get_at_offset(1) == AUTO_TRANSLATE_TYPE {
+                    self.make_auto_translate_tag();
+                } else {
+                    // TODO: Resource bytes?
+                    self.make_hex_bytes_tag("resource", &self.source_bytes[self.idx..self.idx + 5]);
+                }
                 self.idx += 6;
                 continue;
             }
@@ -286,47 +291,28 @@ impl<'a> Decoder<'a> {
         let len = self.get_at_offset(0);
         self.idx += 1;
 
-        if self.can_extend(len as usize) {
+        if len > 0 && self.can_extend(len as usize) {
             let tag = prefix_01::decode(self.get_at_offset(0));
 
-            if tag != "" {
-                let block_bytes = &self.source_bytes[self.idx + 1..self.idx + len as usize];
-                let mut values: Vec<String> = vec![];
-                let mut idx = 0;
-
-                // Parse sub blocks
-                while idx < block_bytes.len() {
-                    let sub_len = block_bytes[idx] ^ 0x80;
-                    idx += 1;
-                    let sub_block_bytes = match sub_len {
-                        1 => [block_bytes[idx] ^ 0x80, 0, 0, 0],
-                        2 => [block_bytes[idx] ^ 0x80, block_bytes[idx + 1] ^ 0x80, 0, 0],
-                        4 => [
-                            block_bytes[idx] ^ 0x80,
-                            block_bytes[idx + 1] ^ 0x80,
-                            block_bytes[idx + 2] ^ 0x80,
-                            block_bytes[idx + 3] ^ 0x80,
-                        ],
-                        _ => {
-                            eprintln!("Invalid sub block length {}", sub_len);
-                            [0, 0, 0, 0]
-                        }
-                    };
-                    let value = u32::from_le_bytes(sub_block_bytes);
-                    idx += sub_len as usize + 1; // Skip closing bytes as well.
-                    values.push(format!("{}[{}]", value, sub_len));
-                }
+            let block_bytes = &self.source_bytes[self.idx + 1..self.idx + len as usize];
+            let values = Self::parse_01_values(block_bytes);
 
-                if values.is_empty() {
-                    self.tag_no_params(tag);
-                } else {
-                    self.make_str_tag(tag, &values.join(", "));
+            match values {
+                Some(values) if tag != "" => {
+                    if values.is_empty() {
+                        self.tag_no_params(tag);
+                    } else {
+                        self.make_str_tag(tag, &values.join(", "));
+                    }
+                }
+                _ => {
+                    // Unnamed blocks, or blocks with sub-blocks that can't be represented as values,
+                    // are kept as they are.
+                    self.make_hex_bytes_tag(
+                        "unknown",
+                        &self.source_bytes[self.idx - 2..self.idx + len as usize],
+                    );
                 }
-            } else {
-                self.make_hex_bytes_tag(
-                    "unknown",
-                    &self.source_bytes[self.idx - 2..self.idx + len as usize],
-                );
             }
 
             self.idx += len as usize;
@@ -336,6 +322,41 @@ impl<'a> Decoder<'a> {
         }
     }
 
+    // Sub-blocks have a length of 1 to 4 bytes, which is kept as part of the value,
+    // since the same value can be given with different lengths.
+    fn parse_01_values(block_bytes: &[u8]) -> Option<Vec<String>> {
+        let mut values: Vec<String> = vec![];
+        let mut idx = 0;
+
+        while idx < block_bytes.len() {
+            let sub_len = (block_bytes[idx] ^ 0x80) as usize;
+            idx += 1;
+
+            if !(1..=4).contains(&sub_len) || idx + sub_len >= block_bytes.len() {
+                return None;
+            }
+
+            let mut sub_block_bytes = [0; 4];
+            for (value_byte, byte) in sub_block_bytes
+                .iter_mut()
+                .zip(&block_bytes[idx..idx + sub_len])
+            {
+                *value_byte = byte ^ 0x80;
+            }
+
+            // Sub-blocks end with a zero byte.
+            if block_bytes[idx + sub_len] != 0x80 {
+                return None;
+            }
+
+            let value = u32::from_le_bytes(sub_block_bytes);
+            idx += sub_len + 1;
+            values.push(format!("{}[{}]", value, sub_len));
+        }
+
+        Some(values)
+    }
+
     // Icons
     fn decode_ef(&mut self) {
         let icon_name = icon::decode(self.get_at_offset(0));
@@ -348,6 +369,24 @@ impl<'a> Decoder<'a> {
         }
     }
 
+    // Auto-translate references are shown with the phrase text after the ID, when it is known.
+    // The text is only for readability, and is ignored when encoding.
+    fn make_auto_translate_tag(&mut self) {
+        let id = AutoTranslateId {
+            language: self.get_at_offset(2),
+            category: self.get_at_offset(3),
+            phrase: self.get_at_offset(4),
+        };
+
+        self.tag_open_params("autotranslate");
+        self.push_hex(&id.to_bytes());
+        if let Some(text) = phrase_text(&id).filter(|text| !text.contains(TAG_END)) {
+            self.push_str(" ");
+            self.push_str(&text);
+        }
+        self.tag_close();
+    }
+
     #[inline]
     fn can_extend(&self, amount: usize) -> bool {
         self.idx + amount < self.end_idx
@@ -467,16 +506,33 @@ impl<'a> Decoder<'a> {
                 self.make_hex_bytes_tag("unknown-table-value", &[first_byte, second_byte]);
             } else {
                 // Secondary table decoded character bytes
-                self.decoded_bytes
-                    .extend(secondary_table_value.to_be_bytes());
+                self.push_table_char(secondary_table_value, &[first_byte, second_byte]);
             }
         } else if primary_table_value == 0xFFFF {
             // Unknown table lookup
             self.make_hex_byte_tag("unknown-table", first_byte);
         } else {
             // Regular decoded character bytes
-            self.decoded_bytes.extend(primary_table_value.to_be_bytes());
+            self.push_table_char(primary_table_value, &[first_byte]);
+        }
+    }
+
+    // Some characters are in the tables more than once, like the duplicated symbols and
+    // half-width katakana in the Japanese tables. Only one of them is used when encoding,
+    // so the others are kept as a tag with their bytes, followed by the character for readability.
+    fn push_table_char(&mut self, value: u16, bytes: &[u8]) {
+        if ConversionTable::rev_lookup_bytes(value) == bytes {
+            self.decoded_bytes.extend(value.to_be_bytes());
+            return;
         }
+
+        self.tag_open_params("char-variant");
+        self.push_hex(bytes);
+        if let Some(char) = char::from_u32(value as u32).filter(|char| *char != TAG_END) {
+            self.push_str(" ");
+            self.push_str(&char.to_string());
+        }
+        self.tag_close();
     }
 }
 
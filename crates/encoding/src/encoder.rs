@@ -1,6 +1,7 @@
 use std::{iter::Peekable, str::CharIndices};
 
 use crate::{
+    auto_translate::AUTO_TRANSLATE_TYPE,
     conversion_tables::ConversionTable,
     named_bytes::{base_len_1, icon, prefix_01, prefix_7f_len_1},
     TAG_END, TAG_PARAM_START, TAG_PREFIX, TAG_START,
@@ -207,7 +208,8 @@ impl<'a> Encoder<'a> {
                 if let Some(icon_byte) = icon::encode(content) {
                     self.decoded_bytes.push(icon_byte);
                 } else {
-                    let bytes = (2..content.len())
+                    // Unnamed icons are kept with their 0xEF prefix.
+                    let bytes = (4..content.len())
                         .step_by(2)
                         .map(|idx| u8::from_str_radix(&content[idx..idx + 2], 16).unwrap());
 
@@ -247,6 +249,42 @@ impl<'a> Encoder<'a> {
                 return Ok(());
             }
 
+            "autotranslate" => {
+                // Any phrase text after the ID is only there for readability.
+                let id = content.split_whitespace().next().unwrap_or_default();
+                let bytes = id
+                    .strip_prefix("0x")
+                    .filter(|hex| hex.len() == 8)
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .map(u32::to_be_bytes)
+                    .filter(|bytes| bytes[0] == AUTO_TRANSLATE_TYPE)
+                    .ok_or_else(|| anyhow!("Invalid auto-translate ID '{}'", id))?;
+
+                self.decoded_bytes.push(0xFD);
+                self.decoded_bytes.extend(bytes);
+                self.decoded_bytes.push(0xFD);
+                return Ok(());
+            }
+
+            "char-variant" => {
+                // Any character after the bytes is only there for readability.
+                let value = content.split_whitespace().next().unwrap_or_default();
+                let bytes = value
+                    .strip_prefix("0x")
+                    .filter(|hex| matches!(hex.len(), 2 | 4))
+                    .and_then(|hex| {
+                        let code = u16::from_str_radix(hex, 16).ok()?;
+                        Some(match hex.len() {
+                            2 => vec![code as u8],
+                            _ => code.to_be_bytes().to_vec(),
+                        })
+                    })
+                    .ok_or_else(|| anyhow!("Invalid character variant '{}'", value))?;
+
+                self.decoded_bytes.extend(bytes);
+                return Ok(());
+            }
+
             "unknown" | "unknown-table" | "unknown-table-index" | "unknown-table-value" => {
                 let bytes = (2..content.len())
                     .step_by(2)
@@ -281,14 +319,12 @@ impl<'a> Encoder<'a> {
             } else {
                 let parameters = content
                     .split(',')
-                    .filter_map(|param| {
-                        let parsed_param = Self::parse_param_with_length(param);
-                        if parsed_param.is_none() {
-                            eprintln!("Could not parse param of tag '{}': {}", tag, param);
-                        }
-                        parsed_param
+                    .map(|param| {
+                        Self::parse_param_with_length(param).ok_or_else(|| {
+                            anyhow!("Could not parse param of tag '{}': {}", tag, param)
+                        })
                     })
-                    .collect::<Vec<_>>();
+                    .collect::<Result<Vec<_>>>()?;
 
                 let parameters_len: u32 = parameters.iter().map(|(_, size)| size + 2).sum();
 
@@ -372,6 +408,11 @@ impl<'a> Encoder<'a> {
 
         let len = param[len_start_idx..len_end_idx].parse::<u32>().ok()?;
 
+        // The value has to fit in a sub-block of 1 to 4 bytes.
+        if !(1..=4).contains(&len) || len < 4 && value >> (len * 8) != 0 {
+            return None;
+        }
+
         // Expect closing bracket
         if let Some((_, ch)) = param_iter.next() {
             if ch != ']' {
@@ -385,8 +426,14 @@ impl<'a> Encoder<'a> {
 
 #[cfg(test)]
 mod tests {
-
-    use crate::{decoder::Decoder, encoder::Encoder, tests::example_strings_for_encoding};
+    use std::sync::Arc;
+
+    use crate::{
+        auto_translate::{self, AutoTranslateId, AutoTranslatePhrases},
+        decoder::Decoder,
+        encoder::Encoder,
+        tests::example_strings_for_encoding,
+    };
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -458,4 +505,99 @@ mod tests {
             "item singular roundtrip",
         );
     }
+
+    #[test]
+    fn auto_translate() {
+        check_encoding_and_roundtrip(
+            &[0xFD, 0x02, 0x02, 0x0A, 0x01, 0xFD, 0x00, 0x07],
+            "${autotranslate: 0x02020A01}",
+            "auto-translate without phrase text",
+        );
+
+        assert_eq!(
+            Encoder::encode_dialog("${autotranslate: 0x02020A01 Hello!}").unwrap(),
+            [0xFD, 0x02, 0x02, 0x0A, 0x01, 0xFD, 0x00, 0x07],
+            "auto-translate with phrase text"
+        );
+
+        assert!(Encoder::encode_dialog("${autotranslate: 0x0A01}").is_err());
+
+        let bytes = [0xFD, 0x02, 0x02, 0x0A, 0x01, 0xFD, 0x00, 0x07];
+        let phrases = Arc::new(AutoTranslatePhrases::from([(
+            AutoTranslateId {
+                language: 0x02,
+                category: 0x0A,
+                phrase: 0x01,
+            },
+            "Hello!".to_string(),
+        )]));
+        assert_eq!(
+            auto_translate::with_phrases(phrases, || Decoder::decode_dialog(&bytes)).unwrap(),
+            "${autotranslate: 0x02020A01 Hello!}",
+            "auto-translate with known phrases"
+        );
+        assert_eq!(
+            Decoder::decode_dialog(&bytes).unwrap(),
+            "${autotranslate: 0x02020A01}",
+            "phrases are only shown within with_phrases"
+        );
+    }
+
+    #[test]
+    fn block_lengths() {
+        check_encoding_and_roundtrip(
+            &[0x01, 0x06, 0x24, 0x83, 0x81, 0x82, 0x83, 0x80, 0x00, 0x07],
+            "${item-article: 197121[3]}",
+            "3 byte value",
+        );
+
+        check_encoding_and_roundtrip(
+            &[0x01, 0x05, 0x24, 0x82, 0x81, 0x80, 0x81, 0x00, 0x07],
+            "${unknown: 0x01052482818081}",
+            "sub-block without ending",
+        );
+
+        assert!(Encoder::encode_dialog("${item-article: 256[1]}").is_err());
+        assert!(Encoder::encode_dialog("${item-article: 1[5]}").is_err());
+    }
+
+    #[test]
+    fn japanese() {
+        check_encoding_and_roundtrip(
+            &[
+                0x83, 0x4E, 0x83, 0x8A, 0x83, 0x58, 0x83, 0x5E, 0x83, 0x8B, 0x00, 0x07,
+            ],
+            "クリスタル",
+            "full-width katakana",
+        );
+
+        check_encoding_and_roundtrip(
+            &[0xB8, 0xD8, 0xBD, 0xC0, 0xD9, 0x00, 0x07],
+            "ｸﾘｽﾀﾙ",
+            "half-width katakana",
+        );
+
+        check_encoding_and_roundtrip(
+            &[0x81, 0xE6, 0x87, 0x9A, 0x00, 0x07],
+            "${char-variant: 0x81E6 ∵}${char-variant: 0x879A ∵}",
+            "duplicate characters",
+        );
+
+        assert_eq!(
+            Encoder::encode_dialog("${char-variant: 0x81E6}").unwrap(),
+            [0x81, 0xE6, 0x00, 0x07],
+            "duplicate character without readable character"
+        );
+
+        assert_eq!(
+            Encoder::encode_dialog("${char-variant: 0xB8}").unwrap(),
+            [0xB8, 0x00, 0x07],
+            "single-byte duplicate character"
+        );
+    }
+
+    #[test]
+    fn unnamed_icon() {
+        check_encoding_and_roundtrip(&[0xEF, 0x40, 0x00, 0x07], "${icon: 0xEF40}", "unnamed icon");
+    }
 }
@@ -1,4 +1,5 @@
-mod conversion_tables;
+pub mod auto_translate;
+pub mod conversion_tables;
 pub mod decoder;
 pub mod encoder;
 mod named_bytes;
@@ -1,12 +1,18 @@
 use std::{
     fs::{self, File},
     io::{BufReader, BufWriter, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
 use anyhow::{anyhow, Result};
-use dats::{base::Dat, context::DatContext, dat_format::DatFormat};
+use dats::{
+    base::{Dat, PathDat},
+    context::{DatContext, ExtractedDat},
+    dat_format::DatFormat,
+    external_files::ExternalFiles,
+};
+use encoding::auto_translate;
 use serde::Serialize;
 
 use crate::dat_descriptor::DatUsage;
@@ -16,14 +22,12 @@ pub(crate) struct DatToYamlConverter {
     pub raw_data_path: PathBuf,
 }
 
-impl DatUsage for DatToYamlConverter {
-    fn use_dat<T: DatFormat + Serialize + for<'b> serde::Deserialize<'b>>(
-        self,
-        dat: Dat<T>,
-    ) -> Result<PathBuf> {
-        let data = self.dat_context.get_data_from_dat(&dat)?;
-
+impl DatToYamlConverter {
+    fn write_yaml<T: DatFormat + Serialize>(self, mut data: ExtractedDat<T>) -> Result<PathBuf> {
         fs::create_dir_all(&self.raw_data_path.parent().unwrap())?;
+        data.dat
+            .export_external_files(&ExternalFiles::for_raw_data_path(&self.raw_data_path)?)?;
+
         let file = File::create(&self.raw_data_path).map_err(|err| {
             anyhow!(
                 "Could not create at file {}: {}",
@@ -38,18 +42,46 @@ impl DatUsage for DatToYamlConverter {
     }
 }
 
+impl DatUsage for DatToYamlConverter {
+    fn use_dat<T: DatFormat + Serialize + for<'b> serde::Deserialize<'b>>(
+        self,
+        dat: Dat<T>,
+    ) -> Result<PathBuf> {
+        // Text is decoded with the phrases of the context's language, such that
+        // auto-translate references show their text. Without the phrases, the
+        // references are still written with their ids.
+        let data = match self.dat_context.auto_translate_phrases() {
+            Ok(phrases) => {
+                auto_translate::with_phrases(phrases, || self.dat_context.get_data_from_dat(&dat))?
+            }
+            Err(err) => {
+                eprintln!("Decoding without auto-translate phrases: {err}");
+                self.dat_context.get_data_from_dat(&dat)?
+            }
+        };
+        self.write_yaml(data)
+    }
+
+    fn use_path_dat<T: DatFormat + Serialize + for<'b> serde::Deserialize<'b>>(
+        self,
+        dat: PathDat<T>,
+    ) -> Result<PathBuf> {
+        let data = self.dat_context.get_data_from_path_dat(&dat)?;
+        self.write_yaml(data)
+    }
+}
+
 pub(crate) struct YamlToDatConverter {
     pub dat_context: Arc<DatContext>,
     pub raw_data_path: PathBuf,
     pub dat_root_path: PathBuf,
 }
 
-impl DatUsage for YamlToDatConverter {
-    fn use_dat<T: DatFormat + Serialize + for<'a> serde::Deserialize<'a>>(
+impl YamlToDatConverter {
+    fn write_dat<T: DatFormat + for<'a> serde::Deserialize<'a>>(
         self,
-        dat: Dat<T>,
+        relative_dat_path: &Path,
     ) -> Result<PathBuf> {
-        let relative_dat_path = dat.get_relative_dat_path(&self.dat_context)?;
         let dat_path = self.dat_root_path.join(relative_dat_path);
 
         fs::create_dir_all(&dat_path.parent().unwrap())?;
@@ -63,10 +95,28 @@ impl DatUsage for YamlToDatConverter {
                 err
             )
         })?;
-        let data: T = serde_yaml::from_reader(BufReader::new(raw_data_file))?;
+        let mut data: T = serde_yaml::from_reader(BufReader::new(raw_data_file))?;
+        data.import_external_files(&ExternalFiles::for_raw_data_path(&self.raw_data_path)?)?;
 
         dat_file.write_all(&data.to_bytes()?)?;
 
         Ok(dat_path)
     }
 }
+
+impl DatUsage for YamlToDatConverter {
+    fn use_dat<T: DatFormat + Serialize + for<'a> serde::Deserialize<'a>>(
+        self,
+        dat: Dat<T>,
+    ) -> Result<PathBuf> {
+        let relative_dat_path = dat.get_relative_dat_path(&self.dat_context)?;
+        self.write_dat::<T>(&relative_dat_path)
+    }
+
+    fn use_path_dat<T: DatFormat + Serialize + for<'a> serde::Deserialize<'a>>(
+        self,
+        dat: PathDat<T>,
+    ) -> Result<PathBuf> {
+        self.write_dat::<T>(dat.relative_path())
+    }
+}
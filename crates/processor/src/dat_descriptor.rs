@@ -1,8 +1,12 @@
 use anyhow::{anyhow, Result};
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use dats::{
-    base::{Dat, ZoneId},
+    base::{Dat, PathDat, ZoneId},
     context::DatContext,
     dat_format::DatFormat,
     id_mapping::DatIdMapping,
@@ -17,12 +21,14 @@ use crate::converters::{DatToYamlConverter, YamlToDatConverter};
 #[serde(tag = "type", content = "index")]
 pub enum DatDescriptor {
     DataMenu,
+    Font,
 
     // String tables
     AbilityNames,
     AbilityDescriptions,
     AreaNames,
     AreaNamesAlt,
+    AutoTranslate,
     CharacterSelect,
     ChatFilterTypes,
     DayNames,
@@ -35,8 +41,10 @@ pub enum DatDescriptor {
     KeyItems,
     MenuItemsDescription,
     MenuItemsText,
+    MissionLog,
     MoonPhases,
     PolMessages,
+    QuestLog,
     RaceNames,
     RegionNames,
     SpellNames,
@@ -45,8 +53,13 @@ pub enum DatDescriptor {
     StatusNames,
     TimeAndPronouns,
     Titles,
+    // Misc DATs are numbered until what they hold is identified.
     Misc1,
     Misc2,
+    Misc3,
+    Misc4,
+    Misc5,
+    Misc6,
     WeatherTypes,
 
     // Item data
@@ -76,6 +89,13 @@ pub enum DatDescriptor {
     EntityNames(ZoneId),
     Dialog(ZoneId),
     Dialog2(ZoneId),
+
+    // Dats by zone and floor
+    Minimap(ZoneId, u8),
+
+    // Sound files
+    Music(u16),
+    SoundEffect(u32),
 }
 
 pub trait DatUsage {
@@ -83,6 +103,11 @@ pub trait DatUsage {
         self,
         dat: Dat<T>,
     ) -> Result<PathBuf>;
+
+    fn use_path_dat<T: DatFormat + Serialize + for<'a> serde::Deserialize<'a>>(
+        self,
+        dat: PathDat<T>,
+    ) -> Result<PathBuf>;
 }
 
 impl DatDescriptor {
@@ -92,10 +117,13 @@ impl DatDescriptor {
         raw_data_root_path: PathBuf,
     ) -> Result<PathBuf> {
         let data_path = raw_data_root_path.join(self.get_relative_path(&dat_context)? + ".yml");
-        self.convert_with(DatToYamlConverter {
-            dat_context,
-            raw_data_path: data_path,
-        })
+        self.convert_with(
+            dat_context.id_mapping(),
+            DatToYamlConverter {
+                dat_context,
+                raw_data_path: data_path,
+            },
+        )
     }
 
     pub fn yaml_to_dat(
@@ -105,11 +133,14 @@ impl DatDescriptor {
         dat_root_path: PathBuf,
     ) -> Result<PathBuf> {
         let raw_data_path = raw_data_root_path.join(self.get_relative_path(&dat_context)? + ".yml");
-        self.convert_with(YamlToDatConverter {
-            dat_context,
-            raw_data_path,
-            dat_root_path,
-        })
+        self.convert_with(
+            dat_context.id_mapping(),
+            YamlToDatConverter {
+                dat_context,
+                raw_data_path,
+                dat_root_path,
+            },
+        )
     }
 
     fn get_zoned_file_name(
@@ -131,11 +162,13 @@ impl DatDescriptor {
     fn get_relative_path(&self, dat_context: &DatContext) -> Result<String> {
         match self {
             DatDescriptor::DataMenu => Ok("data_menu".to_string()),
+            DatDescriptor::Font => Ok("font".to_string()),
 
             DatDescriptor::AbilityNames => Ok("ability_names".to_string()),
             DatDescriptor::AbilityDescriptions => Ok("ability_descriptions".to_string()),
             DatDescriptor::AreaNames => Ok("area_names".to_string()),
             DatDescriptor::AreaNamesAlt => Ok("area_names_alt".to_string()),
+            DatDescriptor::AutoTranslate => Ok("auto_translate".to_string()),
             DatDescriptor::CharacterSelect => Ok("character_select".to_string()),
             DatDescriptor::ChatFilterTypes => Ok("chat_filter_types".to_string()),
             DatDescriptor::DayNames => Ok("day_names".to_string()),
@@ -148,8 +181,10 @@ impl DatDescriptor {
             DatDescriptor::KeyItems => Ok("key_items".to_string()),
             DatDescriptor::MenuItemsDescription => Ok("menu_items_description".to_string()),
             DatDescriptor::MenuItemsText => Ok("menu_items_text".to_string()),
+            DatDescriptor::MissionLog => Ok("mission_log".to_string()),
             DatDescriptor::MoonPhases => Ok("moon_phases".to_string()),
             DatDescriptor::PolMessages => Ok("pol_messages".to_string()),
+            DatDescriptor::QuestLog => Ok("quest_log".to_string()),
             DatDescriptor::RaceNames => Ok("race_names".to_string()),
             DatDescriptor::RegionNames => Ok("region_names".to_string()),
             DatDescriptor::SpellNames => Ok("spell_names".to_string()),
@@ -160,6 +195,10 @@ impl DatDescriptor {
             DatDescriptor::Titles => Ok("titles".to_string()),
             DatDescriptor::Misc1 => Ok("misc1".to_string()),
             DatDescriptor::Misc2 => Ok("misc2".to_string()),
+            DatDescriptor::Misc3 => Ok("misc3".to_string()),
+            DatDescriptor::Misc4 => Ok("misc4".to_string()),
+            DatDescriptor::Misc5 => Ok("misc5".to_string()),
+            DatDescriptor::Misc6 => Ok("misc6".to_string()),
             DatDescriptor::WeatherTypes => Ok("weather_types".to_string()),
 
             DatDescriptor::Armor => Ok("items/armor".to_string()),
@@ -192,6 +231,15 @@ impl DatDescriptor {
             DatDescriptor::Dialog2(zone_id) => {
                 Self::get_zoned_file_name(dat_context, "dialog2", zone_id)
             }
+
+            DatDescriptor::Minimap(zone_id, floor) => Ok(format!(
+                "{}_{}",
+                Self::get_zoned_file_name(dat_context, "minimaps", zone_id)?,
+                floor
+            )),
+
+            DatDescriptor::Music(id) => Ok(format!("music/music{:03}", id)),
+            DatDescriptor::SoundEffect(id) => Ok(format!("sound_effects/se{:06}", id)),
         }
     }
 
@@ -199,6 +247,22 @@ impl DatDescriptor {
         dat_context.zone_name_to_id_map.get(zone_dir_name).copied()
     }
 
+    /// Minimaps are named after their zone and floor, like `West_Ronfaure_0`.
+    fn get_minimap(file_name: &str, dat_context: &DatContext) -> Option<Self> {
+        let (zone_name, floor) = file_name.rsplit_once('_')?;
+
+        Some(DatDescriptor::Minimap(
+            Self::get_zone_id(zone_name, dat_context)?,
+            floor.parse().ok()?,
+        ))
+    }
+
+    /// Whether the path is a raw data file, as opposed to a file that is kept next to one.
+    pub fn is_raw_data_file(path: &Path) -> bool {
+        path.extension() == Some(OsStr::new("yml"))
+    }
+
+    /// Maps a raw data file, or a file in the directory next to one, to its DAT.
     pub fn from_path(
         path: &PathBuf,
         raw_data_dir: &PathBuf,
@@ -206,6 +270,15 @@ impl DatDescriptor {
     ) -> Option<Self> {
         let path = path.strip_prefix(raw_data_dir).unwrap_or(path);
 
+        // Files like icons are kept in a directory named after the raw data file they belong to.
+        if !Self::is_raw_data_file(path) {
+            return path.ancestors().skip(1).find_map(|dir| {
+                let dir_name = dir.file_name()?.to_str()?;
+                let raw_data_path = dir.with_file_name(format!("{dir_name}.yml"));
+                Self::from_path(&raw_data_path, raw_data_dir, dat_context)
+            });
+        }
+
         let file_name = path
             .file_name()
             .and_then(|osstr| osstr.to_str())
@@ -223,6 +296,15 @@ impl DatDescriptor {
                 }
                 "dialog" => Self::get_zone_id(file_name, dat_context).map(DatDescriptor::Dialog),
                 "dialog2" => Self::get_zone_id(file_name, dat_context).map(DatDescriptor::Dialog2),
+                "minimaps" => Self::get_minimap(file_name, dat_context),
+                "music" => file_name
+                    .strip_prefix("music")
+                    .and_then(|id| id.parse().ok())
+                    .map(DatDescriptor::Music),
+                "sound_effects" => file_name
+                    .strip_prefix("se")
+                    .and_then(|id| id.parse().ok())
+                    .map(DatDescriptor::SoundEffect),
 
                 "items" => match file_name {
                     "armor" => Some(DatDescriptor::Armor),
@@ -249,21 +331,20 @@ impl DatDescriptor {
                     "unity_dialogs" => Some(DatDescriptor::UnityDialogs),
                     _ => None,
                 },
-                _ => {
-                    println!("Parent is: {}", parent);
-                    None
-                }
+                _ => None,
             };
         }
 
         // Files in root directory
         match file_name {
             "data_menu" => Some(DatDescriptor::DataMenu),
+            "font" => Some(DatDescriptor::Font),
 
             "ability_names" => Some(DatDescriptor::AbilityNames),
             "ability_descriptions" => Some(DatDescriptor::AbilityDescriptions),
             "area_names" => Some(DatDescriptor::AreaNames),
             "area_names_alt" => Some(DatDescriptor::AreaNamesAlt),
+            "auto_translate" => Some(DatDescriptor::AutoTranslate),
             "character_select" => Some(DatDescriptor::CharacterSelect),
             "chat_filter_types" => Some(DatDescriptor::ChatFilterTypes),
             "day_names" => Some(DatDescriptor::DayNames),
@@ -277,8 +358,10 @@ impl DatDescriptor {
             "menu" => Some(DatDescriptor::DataMenu),
             "menu_items_description" => Some(DatDescriptor::MenuItemsDescription),
             "menu_items_text" => Some(DatDescriptor::MenuItemsText),
+            "mission_log" => Some(DatDescriptor::MissionLog),
             "moon_phases" => Some(DatDescriptor::MoonPhases),
             "pol_messages" => Some(DatDescriptor::PolMessages),
+            "quest_log" => Some(DatDescriptor::QuestLog),
             "race_names" => Some(DatDescriptor::RaceNames),
             "region_names" => Some(DatDescriptor::RegionNames),
             "spell_names" => Some(DatDescriptor::SpellNames),
@@ -289,139 +372,142 @@ impl DatDescriptor {
             "titles" => Some(DatDescriptor::Titles),
             "misc1" => Some(DatDescriptor::Misc1),
             "misc2" => Some(DatDescriptor::Misc2),
+            "misc3" => Some(DatDescriptor::Misc3),
+            "misc4" => Some(DatDescriptor::Misc4),
+            "misc5" => Some(DatDescriptor::Misc5),
+            "misc6" => Some(DatDescriptor::Misc6),
             "weather_types" => Some(DatDescriptor::WeatherTypes),
 
             _ => None,
         }
     }
 
-    fn convert_with<T: DatUsage>(self, converter: T) -> Result<PathBuf> {
+    /// The DAT of content that is not mapped for every language.
+    fn mapped<T: DatFormat>(&self, dat: &Option<Dat<T>>) -> Result<Dat<T>> {
+        dat.clone().ok_or(anyhow!(
+            "No DAT is mapped for {:?} in the language of the project yet.",
+            self
+        ))
+    }
+
+    fn convert_with<T: DatUsage>(self, id_mapping: &DatIdMapping, converter: T) -> Result<PathBuf> {
         match self {
-            DatDescriptor::DataMenu => converter.use_dat(DatIdMapping::get().data_menu.clone()),
+            DatDescriptor::DataMenu => converter.use_dat(id_mapping.data_menu.clone()),
+            DatDescriptor::Font => converter.use_dat(self.mapped(&id_mapping.font)?),
 
-            DatDescriptor::AbilityNames => {
-                converter.use_dat(DatIdMapping::get().ability_names.clone())
-            }
+            DatDescriptor::AbilityNames => converter.use_dat(id_mapping.ability_names.clone()),
             DatDescriptor::AbilityDescriptions => {
-                converter.use_dat(DatIdMapping::get().ability_descriptions.clone())
-            }
-            DatDescriptor::AreaNames => converter.use_dat(DatIdMapping::get().area_names.clone()),
-            DatDescriptor::AreaNamesAlt => {
-                converter.use_dat(DatIdMapping::get().area_names_alt.clone())
+                converter.use_dat(id_mapping.ability_descriptions.clone())
             }
+            DatDescriptor::AreaNames => converter.use_dat(id_mapping.area_names.clone()),
+            DatDescriptor::AreaNamesAlt => converter.use_dat(id_mapping.area_names_alt.clone()),
+            DatDescriptor::AutoTranslate => converter.use_dat(id_mapping.auto_translate.clone()),
             DatDescriptor::CharacterSelect => {
-                converter.use_dat(DatIdMapping::get().character_select.clone())
+                converter.use_dat(id_mapping.character_select.clone())
             }
             DatDescriptor::ChatFilterTypes => {
-                converter.use_dat(DatIdMapping::get().chat_filter_types.clone())
+                converter.use_dat(id_mapping.chat_filter_types.clone())
             }
-            DatDescriptor::DayNames => converter.use_dat(DatIdMapping::get().day_names.clone()),
-            DatDescriptor::Directions => converter.use_dat(DatIdMapping::get().directions.clone()),
+            DatDescriptor::DayNames => converter.use_dat(id_mapping.day_names.clone()),
+            DatDescriptor::Directions => converter.use_dat(id_mapping.directions.clone()),
             DatDescriptor::EquipmentLocations => {
-                converter.use_dat(DatIdMapping::get().equipment_locations.clone())
-            }
-            DatDescriptor::ErrorMessages => {
-                converter.use_dat(DatIdMapping::get().error_messages.clone())
+                converter.use_dat(id_mapping.equipment_locations.clone())
             }
+            DatDescriptor::ErrorMessages => converter.use_dat(id_mapping.error_messages.clone()),
             DatDescriptor::IngameMessages1 => {
-                converter.use_dat(DatIdMapping::get().ingame_messages_1.clone())
+                converter.use_dat(id_mapping.ingame_messages_1.clone())
             }
             DatDescriptor::IngameMessages2 => {
-                converter.use_dat(DatIdMapping::get().ingame_messages_2.clone())
+                converter.use_dat(id_mapping.ingame_messages_2.clone())
             }
-            DatDescriptor::JobNames => converter.use_dat(DatIdMapping::get().job_names.clone()),
-            DatDescriptor::KeyItems => converter.use_dat(DatIdMapping::get().key_items.clone()),
+            DatDescriptor::JobNames => converter.use_dat(id_mapping.job_names.clone()),
+            DatDescriptor::KeyItems => converter.use_dat(id_mapping.key_items.clone()),
             DatDescriptor::MenuItemsDescription => {
-                converter.use_dat(DatIdMapping::get().menu_items_description.clone())
-            }
-            DatDescriptor::MenuItemsText => {
-                converter.use_dat(DatIdMapping::get().menu_items_text.clone())
-            }
-            DatDescriptor::MoonPhases => converter.use_dat(DatIdMapping::get().moon_phases.clone()),
-            DatDescriptor::PolMessages => {
-                converter.use_dat(DatIdMapping::get().pol_messages.clone())
-            }
-            DatDescriptor::RaceNames => converter.use_dat(DatIdMapping::get().race_names.clone()),
-            DatDescriptor::RegionNames => {
-                converter.use_dat(DatIdMapping::get().region_names.clone())
-            }
-            DatDescriptor::SpellNames => converter.use_dat(DatIdMapping::get().spell_names.clone()),
+                converter.use_dat(id_mapping.menu_items_description.clone())
+            }
+            DatDescriptor::MenuItemsText => converter.use_dat(id_mapping.menu_items_text.clone()),
+            DatDescriptor::MissionLog => converter.use_dat(id_mapping.mission_log.clone()),
+            DatDescriptor::MoonPhases => converter.use_dat(id_mapping.moon_phases.clone()),
+            DatDescriptor::PolMessages => converter.use_dat(id_mapping.pol_messages.clone()),
+            DatDescriptor::QuestLog => converter.use_dat(id_mapping.quest_log.clone()),
+            DatDescriptor::RaceNames => converter.use_dat(id_mapping.race_names.clone()),
+            DatDescriptor::RegionNames => converter.use_dat(id_mapping.region_names.clone()),
+            DatDescriptor::SpellNames => converter.use_dat(id_mapping.spell_names.clone()),
             DatDescriptor::SpellDescriptions => {
-                converter.use_dat(DatIdMapping::get().spell_descriptions.clone())
-            }
-            DatDescriptor::StatusInfo => converter.use_dat(DatIdMapping::get().status_info.clone()),
-            DatDescriptor::StatusNames => {
-                converter.use_dat(DatIdMapping::get().status_names.clone())
+                converter.use_dat(id_mapping.spell_descriptions.clone())
             }
+            DatDescriptor::StatusInfo => converter.use_dat(self.mapped(&id_mapping.status_info)?),
+            DatDescriptor::StatusNames => converter.use_dat(id_mapping.status_names.clone()),
             DatDescriptor::TimeAndPronouns => {
-                converter.use_dat(DatIdMapping::get().time_and_pronouns.clone())
-            }
-            DatDescriptor::Titles => converter.use_dat(DatIdMapping::get().titles.clone()),
-            DatDescriptor::Misc1 => converter.use_dat(DatIdMapping::get().misc1.clone()),
-            DatDescriptor::Misc2 => converter.use_dat(DatIdMapping::get().misc2.clone()),
-            DatDescriptor::WeatherTypes => {
-                converter.use_dat(DatIdMapping::get().weather_types.clone())
-            }
+                converter.use_dat(self.mapped(&id_mapping.time_and_pronouns)?)
+            }
+            DatDescriptor::Titles => converter.use_dat(id_mapping.titles.clone()),
+            DatDescriptor::Misc1 => converter.use_dat(id_mapping.misc1.clone()),
+            DatDescriptor::Misc2 => converter.use_dat(id_mapping.misc2.clone()),
+            DatDescriptor::Misc3 => converter.use_dat(id_mapping.misc3.clone()),
+            DatDescriptor::Misc4 => converter.use_dat(id_mapping.misc4.clone()),
+            DatDescriptor::Misc5 => converter.use_dat(self.mapped(&id_mapping.misc5)?),
+            DatDescriptor::Misc6 => converter.use_dat(self.mapped(&id_mapping.misc6)?),
+            DatDescriptor::WeatherTypes => converter.use_dat(id_mapping.weather_types.clone()),
 
-            DatDescriptor::Armor => converter.use_dat(DatIdMapping::get().armor.clone()),
-            DatDescriptor::Armor2 => converter.use_dat(DatIdMapping::get().armor2.clone()),
-            DatDescriptor::Currency => converter.use_dat(DatIdMapping::get().currency.clone()),
-            DatDescriptor::GeneralItems => {
-                converter.use_dat(DatIdMapping::get().general_items.clone())
-            }
-            DatDescriptor::GeneralItems2 => {
-                converter.use_dat(DatIdMapping::get().general_items2.clone())
-            }
-            DatDescriptor::PuppetItems => {
-                converter.use_dat(DatIdMapping::get().puppet_items.clone())
-            }
-            DatDescriptor::UsableItems => {
-                converter.use_dat(DatIdMapping::get().usable_items.clone())
-            }
-            DatDescriptor::Weapons => converter.use_dat(DatIdMapping::get().weapons.clone()),
+            DatDescriptor::Armor => converter.use_dat(id_mapping.armor.clone()),
+            DatDescriptor::Armor2 => converter.use_dat(id_mapping.armor2.clone()),
+            DatDescriptor::Currency => converter.use_dat(self.mapped(&id_mapping.currency)?),
+            DatDescriptor::GeneralItems => converter.use_dat(id_mapping.general_items.clone()),
+            DatDescriptor::GeneralItems2 => converter.use_dat(id_mapping.general_items2.clone()),
+            DatDescriptor::PuppetItems => converter.use_dat(id_mapping.puppet_items.clone()),
+            DatDescriptor::UsableItems => converter.use_dat(id_mapping.usable_items.clone()),
+            DatDescriptor::Weapons => converter.use_dat(id_mapping.weapons.clone()),
             DatDescriptor::VouchersAndSlips => {
-                converter.use_dat(DatIdMapping::get().vouchers_and_slips.clone())
-            }
-            DatDescriptor::Monipulator => {
-                converter.use_dat(DatIdMapping::get().monipulator.clone())
+                converter.use_dat(id_mapping.vouchers_and_slips.clone())
             }
-            DatDescriptor::Instincts => converter.use_dat(DatIdMapping::get().instincts.clone()),
+            DatDescriptor::Monipulator => converter.use_dat(id_mapping.monipulator.clone()),
+            DatDescriptor::Instincts => converter.use_dat(id_mapping.instincts.clone()),
 
             // Global dialog
             DatDescriptor::MonsterSkillNames => {
-                converter.use_dat(DatIdMapping::get().monster_skill_names.clone())
+                converter.use_dat(self.mapped(&id_mapping.monster_skill_names)?)
             }
             DatDescriptor::StatusNamesDialog => {
-                converter.use_dat(DatIdMapping::get().status_names_dialog.clone())
+                converter.use_dat(self.mapped(&id_mapping.status_names_dialog)?)
             }
             DatDescriptor::EmoteMessages => {
-                converter.use_dat(DatIdMapping::get().emote_messages.clone())
+                converter.use_dat(self.mapped(&id_mapping.emote_messages)?)
             }
             DatDescriptor::SystemMessages1 => {
-                converter.use_dat(DatIdMapping::get().system_messages_1.clone())
+                converter.use_dat(self.mapped(&id_mapping.system_messages_1)?)
             }
             DatDescriptor::SystemMessages2 => {
-                converter.use_dat(DatIdMapping::get().system_messages_2.clone())
+                converter.use_dat(self.mapped(&id_mapping.system_messages_2)?)
             }
             DatDescriptor::SystemMessages3 => {
-                converter.use_dat(DatIdMapping::get().system_messages_3.clone())
+                converter.use_dat(self.mapped(&id_mapping.system_messages_3)?)
             }
             DatDescriptor::SystemMessages4 => {
-                converter.use_dat(DatIdMapping::get().system_messages_4.clone())
+                converter.use_dat(self.mapped(&id_mapping.system_messages_4)?)
             }
             DatDescriptor::UnityDialogs => {
-                converter.use_dat(DatIdMapping::get().unity_dialogs.clone())
+                converter.use_dat(self.mapped(&id_mapping.unity_dialogs)?)
             }
 
             // By zone
             DatDescriptor::EntityNames(zone_id) => {
-                converter.use_dat(DatIdMapping::get().entities.get_result(&zone_id)?.clone())
+                converter.use_dat(id_mapping.entities.get_result(&zone_id)?.clone())
             }
             DatDescriptor::Dialog(zone_id) => {
-                converter.use_dat(DatIdMapping::get().dialog.get_result(&zone_id)?.clone())
+                converter.use_dat(id_mapping.dialog.get_result(&zone_id)?.clone())
             }
             DatDescriptor::Dialog2(zone_id) => {
-                converter.use_dat(DatIdMapping::get().dialog.get_result(&zone_id)?.clone())
+                converter.use_dat(id_mapping.dialog2.get_result(&zone_id)?.clone())
+            }
+
+            DatDescriptor::Minimap(zone_id, floor) => {
+                converter.use_dat(id_mapping.minimaps.get_result(&zone_id, floor)?.clone())
+            }
+
+            DatDescriptor::Music(id) => converter.use_path_dat(DatIdMapping::music(id)),
+            DatDescriptor::SoundEffect(id) => {
+                converter.use_path_dat(DatIdMapping::sound_effect(id))
             }
         }
     }
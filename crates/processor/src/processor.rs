@@ -139,6 +139,11 @@ impl DatProcessor {
                     return None;
                 }
 
+                // Files next to the raw data files, like icons, are converted along with them.
+                if !DatDescriptor::is_raw_data_file(entry.path()) {
+                    return None;
+                }
+
                 let path = entry.into_path();
                 let dat_descriptor = DatDescriptor::from_path(&path, &in_dir, &dat_context);
 